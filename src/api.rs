@@ -1,9 +1,17 @@
 #[derive(Clone, Debug, Default)]
-pub struct Api {}
+pub struct Api {
+    /** See [`crate::Config::base_url`] */
+    base_url: Option<String>,
+    /** See [`crate::Config::middlewares`] */
+    middlewares: crate::middleware::Chain,
+}
+
+/** Default root of the API URLs */
+const BASE_URL: &str = "https://api.sumup.com";
 
 macro_rules! url {
     ($path:literal) => {
-        concat!("https://api.sumup.com", $path)
+        $path
     };
     ($path:literal, $( $param:expr ),+ ) => {
         &vec![
@@ -18,13 +26,32 @@ impl Api {
         Self::default()
     }
 
+    #[must_use]
+    pub fn base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url.map(|x| x.trim_end_matches('/').to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn middlewares(mut self, middlewares: crate::middleware::Chain) -> Self {
+        self.middlewares = middlewares;
+        self
+    }
+
     pub fn token(&self, payload: impl serde::Serialize) -> crate::Result<crate::AccessToken> {
-        Self::send(reqwest::Method::POST, url!("/token"), Some(payload), None)
+        self.send(
+            reqwest::Method::POST,
+            "/token",
+            url!("/token"),
+            Some(payload),
+            None,
+        )
     }
 
     pub fn account_get(&self, access_token: &crate::AccessToken) -> crate::Result<crate::Account> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me",
             url!("/v0.1/me"),
             None::<()>,
             Some(access_token),
@@ -36,12 +63,14 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::POST,
+            "/checkouts",
             url!("/checkouts"),
             Some(payload),
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn checkout_list(
@@ -49,8 +78,9 @@ impl Api {
         checkout_reference: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::Checkout>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/checkouts",
             &format!(
                 "{}?checkout_reference={checkout_reference}",
                 url!("/v0.1/checkouts")
@@ -65,8 +95,9 @@ impl Api {
         id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Checkout> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/checkouts/{id}",
             url!("/checkouts", id),
             None::<()>,
             Some(access_token),
@@ -78,8 +109,9 @@ impl Api {
         reference_id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Checkout> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/checkouts",
             &format!("{}?checkout_reference={}", url!("/checkouts"), reference_id),
             None::<()>,
             Some(access_token),
@@ -87,12 +119,14 @@ impl Api {
     }
 
     pub fn checkout_delete(&self, id: &str, access_token: &crate::AccessToken) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::DELETE,
+            "/checkouts/{id}",
             url!("/checkouts", id),
             None::<()>,
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn checkout_update(
@@ -101,12 +135,14 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::PUT,
+            "/checkouts/{id}",
             url!("/checkouts", id),
             Some(payload),
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn customer_create(
@@ -114,12 +150,14 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::POST,
+            "/v0.1/customers",
             url!("/v0.1/customers"),
             Some(payload),
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn customer_update(
@@ -128,8 +166,9 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Customer> {
-        Self::send(
+        self.send(
             reqwest::Method::PUT,
+            "/v0.1/customers/{id}",
             url!("/v0.1/customers", id),
             Some(payload),
             Some(access_token),
@@ -141,8 +180,9 @@ impl Api {
         id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Customer> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/customers/{id}",
             url!("/v0.1/customers", id),
             None::<()>,
             Some(access_token),
@@ -153,9 +193,10 @@ impl Api {
         &self,
         customer_id: &str,
         access_token: &crate::AccessToken,
-    ) -> crate::Result<Vec<crate::Card>> {
-        Self::send(
+    ) -> crate::Result<Vec<crate::PaymentInstrument>> {
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/customers/{customer_id}/payment-instruments",
             url!("/v0.1/customers", customer_id, "payment-instruments"),
             None::<()>,
             Some(access_token),
@@ -167,9 +208,10 @@ impl Api {
         customer_id: &str,
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
-    ) -> crate::Result<crate::Card> {
-        Self::send(
+    ) -> crate::Result<crate::PaymentInstrument> {
+        self.send(
             reqwest::Method::POST,
+            "/v0.1/customers/{customer_id}/payment-instruments",
             url!("/v0.1/customers", customer_id, "payment-instruments"),
             Some(payload),
             Some(access_token),
@@ -182,12 +224,14 @@ impl Api {
         card_token: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::DELETE,
+            "/customers/{customer_id}/payment-instruments/{card_token}",
             url!("/customers", customer_id, "payment-instruments", card_token),
             None::<()>,
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn merchants_payment_methods(
@@ -207,15 +251,23 @@ impl Api {
             url.push_str(&format!("currency={currency}"));
         }
 
-        Self::send(reqwest::Method::GET, &url, None::<()>, Some(access_token))
+        self.send_merchant(
+            reqwest::Method::GET,
+            "/v0.1/merchants/{merchant_code}/payment-methods",
+            &url,
+            None::<()>,
+            Some(access_token),
+            merchant_code,
+        )
     }
 
     pub fn personal_get(
         &self,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::PersonalProfile> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/personal-profile",
             url!("/v0.1/me/personal-profile"),
             None::<()>,
             Some(access_token),
@@ -223,8 +275,9 @@ impl Api {
     }
 
     pub fn profile_get(&self, access_token: &crate::AccessToken) -> crate::Result<crate::Profile> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/merchant-profile",
             url!("/v0.1/me/merchant-profile"),
             None::<()>,
             Some(access_token),
@@ -236,20 +289,23 @@ impl Api {
         profile: &crate::Profile,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
+        self.send::<serde::de::IgnoredAny>(
             reqwest::Method::PUT,
+            "/v0.1/me/merchant-profile",
             url!("/v0.1/me/merchant-profile"),
             Some(profile),
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn profile_doing_business_as_get(
         &self,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::DoingBusinessAs> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/merchant-profile/doing-business-as",
             url!("/v0.1/me/merchant-profile/doing-business-as"),
             None::<()>,
             Some(access_token),
@@ -261,8 +317,9 @@ impl Api {
         dba: &crate::DoingBusinessAs,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::DoingBusinessAs> {
-        Self::send(
+        self.send(
             reqwest::Method::PUT,
+            "/v0.1/me/merchant-profile/doing-business-as",
             url!("/v0.1/me/merchant-profile/doing-business-as"),
             Some(dba),
             Some(access_token),
@@ -273,8 +330,9 @@ impl Api {
         &self,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::BankAccount>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/merchant-profile/bank-accounts",
             url!("/v0.1/me/merchant-profile/bank-accounts"),
             None::<()>,
             Some(access_token),
@@ -285,8 +343,9 @@ impl Api {
         &self,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Settings> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/merchant-profile/settings",
             url!("/v0.1/me/merchant-profile/settings"),
             None::<()>,
             Some(access_token),
@@ -298,8 +357,9 @@ impl Api {
         filter: &crate::services::payouts::Filter,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::Payout>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/financials/payouts",
             &format!(
                 "{}?{}",
                 url!("/v0.1/me/financials/payouts"),
@@ -315,8 +375,9 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::SubAccount> {
-        Self::send(
+        self.send(
             reqwest::Method::POST,
+            "/v0.1/me/accounts",
             url!("/v0.1/me/accounts"),
             Some(payload),
             Some(access_token),
@@ -328,8 +389,9 @@ impl Api {
         id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::SubAccount> {
-        Self::send(
+        self.send(
             reqwest::Method::DELETE,
+            "/v0.1/me/accounts/{id}",
             url!("/v0.1/me/accounts", id),
             None::<()>,
             Some(access_token),
@@ -340,8 +402,9 @@ impl Api {
         &self,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::SubAccount>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/accounts",
             url!("/v0.1/me/accounts"),
             None::<()>,
             Some(access_token),
@@ -354,8 +417,9 @@ impl Api {
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::SubAccount> {
-        Self::send(
+        self.send(
             reqwest::Method::PUT,
+            "/v0.1/me/accounts/{id}",
             url!("/v0.1/me/accounts", id),
             Some(payload),
             Some(access_token),
@@ -367,8 +431,9 @@ impl Api {
         filter: &crate::services::payouts::Filter,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::Transaction>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/financials/transactions",
             &format!(
                 "{}?{}",
                 url!("/v0.1/me/financials/transactions"),
@@ -384,8 +449,9 @@ impl Api {
         id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Transaction> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/transactions",
             &format!("{}?id={id}", url!("/v0.1/me/transactions")),
             None::<()>,
             Some(access_token),
@@ -397,8 +463,9 @@ impl Api {
         internal_id: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Transaction> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/transactions",
             &format!(
                 "{}?internal_id={internal_id}",
                 url!("/v0.1/me/transactions")
@@ -413,8 +480,9 @@ impl Api {
         transaction_code: &str,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Transaction> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/transactions",
             &format!(
                 "{}?transaction_code={transaction_code}",
                 url!("/v0.1/me/transactions")
@@ -429,11 +497,12 @@ impl Api {
         filter: &crate::services::transactions::Filter,
         access_token: &crate::AccessToken,
     ) -> crate::Result<Vec<crate::Transaction>> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/v0.1/me/transactions/history",
             &format!(
                 "{}?{}",
-                url!("/v0.1/me/financials/payouts"),
+                url!("/v0.1/me/transactions/history"),
                 filter.to_string()
             ),
             None::<()>,
@@ -443,16 +512,18 @@ impl Api {
 
     pub fn transactions_refund(
         &self,
-        id: u32,
+        id: &str,
         payload: impl serde::Serialize,
         access_token: &crate::AccessToken,
     ) -> crate::Result {
-        Self::send(
-            reqwest::Method::GET,
+        self.send::<serde::de::IgnoredAny>(
+            reqwest::Method::POST,
+            "/v0.1/me/refund/{id}",
             url!("/v0.1/me/refund", id),
             Some(payload),
             Some(access_token),
         )
+        .map(|_| ())
     }
 
     pub fn transactions_get_receipt(
@@ -461,47 +532,297 @@ impl Api {
         merchant_id: u32,
         access_token: &crate::AccessToken,
     ) -> crate::Result<crate::Receipt> {
-        Self::send(
+        self.send(
             reqwest::Method::GET,
+            "/receipts/{id}",
             &format!("{}?mid={merchant_id}", url!("/receipts", id)),
             None::<()>,
             Some(access_token),
         )
     }
 
+    /**
+     * Sends the request through the middlewares.
+     *
+     * `path` is appended to the base URL. `endpoint` is its template, like `/checkouts/{id}`,
+     * given to the middlewares and reported in the span of the `tracing` feature instead of the
+     * path, which contains identifiers.
+     */
     fn send<T: serde::de::DeserializeOwned>(
+        &self,
         method: reqwest::Method,
-        url: &str,
+        endpoint: &'static str,
+        path: &str,
         payload: Option<impl serde::Serialize>,
         access_token: Option<&crate::AccessToken>,
     ) -> crate::Result<T> {
-        log::trace!("-> {method:?} {url}");
-
-        let mut request = reqwest::blocking::Client::new().request(method, url);
+        self.send_merchant(method, endpoint, path, payload, access_token, "")
+    }
 
+    /**
+     * Same as [`Self::send`] for the endpoints scoped to a merchant, `merchant_code` is reported
+     * in the span.
+     */
+    fn send_merchant<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        endpoint: &'static str,
+        path: &str,
+        payload: Option<impl serde::Serialize>,
+        access_token: Option<&crate::AccessToken>,
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))] merchant_code: &str,
+    ) -> crate::Result<T> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "sumup.request",
+            otel.name = format!("{method} {endpoint}"),
+            otel.kind = "client",
+            http.request.method = %method,
+            url.template = endpoint,
+            sumup.merchant_code = (!merchant_code.is_empty()).then_some(merchant_code),
+            http.response.status_code = tracing::field::Empty,
+            sumup.latency_ms = tracing::field::Empty,
+            error = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let _enter = span.enter();
+
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+        let mut status = None;
+
+        let mut headers = reqwest::header::HeaderMap::new();
         if let Some(access_token) = access_token {
-            let bearer = access_token.bearer();
-            log::trace!("-> Authorization: {bearer}");
-            request = request.header(reqwest::header::AUTHORIZATION, &bearer);
+            let mut bearer = reqwest::header::HeaderValue::from_str(&access_token.bearer())
+                .map_err(|_| crate::Error::Auth("Invalid access token"))?;
+            bearer.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, bearer);
         }
 
-        if let Some(payload) = payload {
+        let request = crate::middleware::Request {
+            method: method.clone(),
+            endpoint,
+            url: format!("{}{path}", self.base_url.as_deref().unwrap_or(BASE_URL)),
+            headers,
+            body: payload.as_ref().map(serde_json::to_string).transpose()?,
+        };
+
+        let result = self
+            .middlewares
+            .run(request, Self::transport)
+            .and_then(|response| {
+                status = Some(response.status);
+                Self::parse(response.status, &response.headers, response.body)
+            });
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("sumup.latency_ms", start.elapsed().as_millis() as u64);
+            if let Some(status) = status {
+                span.record("http.response.status_code", status);
+            }
+            if let Err(err) = &result {
+                span.record("error", tracing::field::display(err));
+            }
+        }
+
+        result
+    }
+
+    /**
+     * Sends the request once the middlewares are done with it.
+     */
+    fn transport(
+        request: crate::middleware::Request,
+    ) -> crate::Result<crate::middleware::Response> {
+        log::trace!("-> {:?} {}", request.method, request.url);
+
+        let mut builder = reqwest::blocking::Client::new()
+            .request(request.method, &request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
             if log::log_enabled!(log::Level::Trace) {
-                log::trace!("-> {}", serde_json::to_string(&payload)?);
+                log::trace!("-> {}", crate::redact::body(&body));
             }
-            request = request.json(&payload);
+            builder = builder
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body);
+        }
+
+        let response = builder.send()?;
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = response.text()?;
+        if log::log_enabled!(log::Level::Trace) {
+            log::trace!("<- {}", crate::redact::body(&body));
+        }
+
+        Ok(crate::middleware::Response {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /**
+     * Deserializes the response, empty successful responses are accepted for `()`.
+     */
+    fn parse<T: serde::de::DeserializeOwned>(
+        status: u16,
+        headers: &reqwest::header::HeaderMap,
+        content: String,
+    ) -> crate::Result<T> {
+        let content_type = headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .map(ToString::to_string);
+        let is_json = content_type.as_deref().is_none_or(|x| x.contains("json"));
+
+        let unexpected = |content: String| crate::Error::UnexpectedResponse {
+            status,
+            content_type: content_type.clone(),
+            body: truncate(content),
+        };
+
+        let is_success = (200..300).contains(&status);
+
+        if content.trim().is_empty() {
+            let empty = crate::Error::EmptyResponse { status };
+
+            return if is_success {
+                serde_json::from_str("null").map_err(|_| empty)
+            } else {
+                Err(empty)
+            };
         }
 
-        let response = request.send()?;
-        let status = response.status();
-        let content = response.text()?;
-        log::trace!("<- {content}");
+        if !is_json {
+            return Err(unexpected(content));
+        }
 
-        if status.is_success() {
+        if is_success {
             serde_json::from_str(&content).map_err(crate::Error::from)
         } else {
-            let message = serde_json::from_str(&content)?;
-            Err(crate::Error::Api(message))
+            match crate::ApiError::new(status, headers, content.clone()) {
+                Ok(err) => Err(crate::Error::Api(Box::new(err))),
+                Err(_) => Err(unexpected(content)),
+            }
         }
     }
 }
+
+/** Maximum length of the body kept in errors */
+const MAX_BODY_LENGTH: usize = 512;
+
+fn truncate(mut body: String) -> String {
+    if body.len() > MAX_BODY_LENGTH {
+        let mut end = MAX_BODY_LENGTH;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+        body.push('…');
+    }
+
+    body
+}
+
+#[cfg(test)]
+mod test {
+    fn headers(content_type: &str) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_TYPE, content_type.parse().unwrap());
+
+        headers
+    }
+
+    #[test]
+    fn parse_empty() {
+        let headers = reqwest::header::HeaderMap::new();
+
+        assert!(crate::Api::parse::<()>(204, &headers, String::new()).is_ok());
+        assert!(matches!(
+            crate::Api::parse::<crate::Checkout>(200, &headers, String::new()),
+            Err(crate::Error::EmptyResponse { status: 200 })
+        ));
+        assert!(matches!(
+            crate::Api::parse::<()>(500, &headers, String::new()),
+            Err(crate::Error::EmptyResponse { status: 500 })
+        ));
+    }
+
+    #[test]
+    fn parse_html() {
+        let body = format!("<html><body>{}</body></html>", "Bad Gateway ".repeat(100));
+
+        let err = crate::Api::parse::<()>(502, &headers("text/html"), body).unwrap_err();
+
+        let crate::Error::UnexpectedResponse {
+            status,
+            content_type,
+            body,
+        } = &err
+        else {
+            panic!("{err:?}");
+        };
+
+        assert_eq!(*status, 502);
+        assert_eq!(content_type.as_deref(), Some("text/html"));
+        assert!(body.len() <= super::MAX_BODY_LENGTH + '…'.len_utf8());
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn parse_error() {
+        let headers = headers("application/problem+json");
+
+        let err = crate::Api::parse::<()>(
+            404,
+            &headers,
+            r#"{"message": "Not found", "error_code": "NOT_FOUND"}"#.to_string(),
+        )
+        .unwrap_err();
+        assert!(err.is_not_found());
+
+        let err = crate::Api::parse::<()>(400, &headers, "{}".to_string()).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::UnexpectedResponse { status: 400, .. }
+        ));
+    }
+
+    #[test]
+    fn wire_body() -> crate::Result {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(std::io::Error::other)?;
+        let url = format!("http://{}", server.server_addr());
+
+        let thread = std::thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            request.respond(tiny_http::Response::empty(204)).unwrap();
+
+            body
+        });
+
+        let checkout = crate::Checkout {
+            amount: 2.2,
+            currency: "EUR".to_string(),
+            checkout_reference: "1".to_string(),
+            merchant_code: "MH4H92C7".to_string(),
+
+            ..Default::default()
+        };
+        crate::Api::new()
+            .base_url(Some(url))
+            .checkout_create(&checkout, &crate::AccessToken::default())?;
+
+        assert_eq!(
+            thread.join().unwrap(),
+            r#"{"amount":2.2,"currency":"EUR","checkout_reference":"1","merchant_code":"MH4H92C7"}"#
+        );
+
+        Ok(())
+    }
+}
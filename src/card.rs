@@ -0,0 +1,374 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Brand {
+    Amex,
+    DinersClub,
+    Discover,
+    Elo,
+    Hipercard,
+    Jcb,
+    Maestro,
+    Mastercard,
+    Visa,
+}
+
+/**
+ * `(brand, first prefix, last prefix)`, the first matching range wins so co-branded ranges
+ * come before the generic ones.
+ */
+const RANGES: &[(Brand, &str, &str)] = &[
+    (Brand::Elo, "401178", "401179"),
+    (Brand::Elo, "431274", "431274"),
+    (Brand::Elo, "438935", "438935"),
+    (Brand::Elo, "451416", "451416"),
+    (Brand::Elo, "457393", "457393"),
+    (Brand::Elo, "457631", "457632"),
+    (Brand::Elo, "504175", "504175"),
+    (Brand::Elo, "506699", "506778"),
+    (Brand::Elo, "509000", "509999"),
+    (Brand::Elo, "627780", "627780"),
+    (Brand::Elo, "636297", "636297"),
+    (Brand::Elo, "636368", "636368"),
+    (Brand::Elo, "650031", "650033"),
+    (Brand::Elo, "650035", "650051"),
+    (Brand::Elo, "650405", "650439"),
+    (Brand::Elo, "650485", "650538"),
+    (Brand::Elo, "650541", "650598"),
+    (Brand::Elo, "650700", "650718"),
+    (Brand::Elo, "650720", "650727"),
+    (Brand::Elo, "650901", "650978"),
+    (Brand::Elo, "651652", "651679"),
+    (Brand::Elo, "655000", "655019"),
+    (Brand::Elo, "655021", "655058"),
+    (Brand::Hipercard, "384100", "384100"),
+    (Brand::Hipercard, "384140", "384140"),
+    (Brand::Hipercard, "384160", "384160"),
+    (Brand::Hipercard, "606282", "606282"),
+    (Brand::Hipercard, "637095", "637095"),
+    (Brand::Hipercard, "637568", "637568"),
+    (Brand::Hipercard, "637599", "637599"),
+    (Brand::Hipercard, "637609", "637609"),
+    (Brand::Hipercard, "637612", "637612"),
+    (Brand::Amex, "34", "34"),
+    (Brand::Amex, "37", "37"),
+    (Brand::DinersClub, "300", "305"),
+    (Brand::DinersClub, "36", "36"),
+    (Brand::DinersClub, "38", "39"),
+    (Brand::Jcb, "3528", "3589"),
+    (Brand::Discover, "6011", "6011"),
+    (Brand::Discover, "622126", "622925"),
+    (Brand::Discover, "644", "649"),
+    (Brand::Discover, "65", "65"),
+    (Brand::Mastercard, "2221", "2720"),
+    (Brand::Mastercard, "51", "55"),
+    (Brand::Visa, "4", "4"),
+    (Brand::Maestro, "50", "50"),
+    (Brand::Maestro, "56", "69"),
+];
+
+impl Brand {
+    /**
+     * Detects the brand from the first digits, `None` for unknown ranges.
+     */
+    #[must_use]
+    pub fn detect(number: &str) -> Option<Self> {
+        let number = digits(number);
+
+        RANGES
+            .iter()
+            .find(|(_, first, last)| {
+                number
+                    .get(..first.len())
+                    .is_some_and(|prefix| prefix >= *first && prefix <= *last)
+            })
+            .map(|(brand, _, _)| *brand)
+    }
+
+    #[must_use]
+    pub fn lengths(self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            Self::Amex => 15..=15,
+            Self::DinersClub => 14..=19,
+            Self::Elo | Self::Mastercard => 16..=16,
+            Self::Discover | Self::Jcb => 16..=19,
+            Self::Hipercard | Self::Visa => 13..=19,
+            Self::Maestro => 12..=19,
+        }
+    }
+
+    #[must_use]
+    pub fn cvv_length(self) -> usize {
+        match self {
+            Self::Amex => 4,
+            _ => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Name,
+    Number,
+    ExpiryMonth,
+    ExpiryYear,
+    Cvv,
+    ZipCode,
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Name => "name",
+            Self::Number => "number",
+            Self::ExpiryMonth => "expiry_month",
+            Self::ExpiryYear => "expiry_year",
+            Self::Cvv => "cvv",
+            Self::ZipCode => "zip_code",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Missing,
+    /** Not made of digits, or out of range */
+    Invalid,
+    /** Wrong number of digits for the brand */
+    Length,
+    /** Luhn check failed */
+    Checksum,
+    UnknownBrand,
+    Expired,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Missing => "missing",
+            Self::Invalid => "invalid",
+            Self::Length => "wrong length",
+            Self::Checksum => "wrong checksum",
+            Self::UnknownBrand => "unknown brand",
+            Self::Expired => "expired",
+        };
+
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+pub struct FieldError {
+    pub field: Field,
+    pub reason: Reason,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.field, self.reason)
+    }
+}
+
+/**
+ * Checks a card before sending it to SumUp, spaces and dashes in the number are ignored.
+ *
+ * Returns the detected brand, or every failing field.
+ */
+pub fn validate(card: &crate::NewCard) -> Result<Brand, Vec<FieldError>> {
+    let today = crate::date::Date::from_days(crate::date::now().div_euclid(86_400));
+
+    validate_at(card, today.year, today.month)
+}
+
+fn validate_at(card: &crate::NewCard, year: i64, month: u32) -> Result<Brand, Vec<FieldError>> {
+    let mut errors = Vec::new();
+    let mut error = |field, reason| errors.push(FieldError { field, reason });
+
+    if card.name.trim().is_empty() {
+        error(Field::Name, Reason::Missing);
+    }
+
+    let number = digits(&card.number);
+    let brand = Brand::detect(&number);
+
+    if number.is_empty() {
+        error(Field::Number, Reason::Missing);
+    } else if !is_digits(&number) {
+        error(Field::Number, Reason::Invalid);
+    } else if let Some(brand) = brand {
+        if !brand.lengths().contains(&number.len()) {
+            error(Field::Number, Reason::Length);
+        } else if !luhn(&number) {
+            error(Field::Number, Reason::Checksum);
+        }
+    } else {
+        error(Field::Number, Reason::UnknownBrand);
+    }
+
+    let expiry_month = card.expiry_month.trim();
+    let expiry_month = expiry_month
+        .parse::<u32>()
+        .ok()
+        .filter(|x| is_digits(expiry_month) && (1..=12).contains(x));
+    if card.expiry_month.trim().is_empty() {
+        error(Field::ExpiryMonth, Reason::Missing);
+    } else if expiry_month.is_none() {
+        error(Field::ExpiryMonth, Reason::Invalid);
+    }
+
+    let expiry_year = card.expiry_year.trim();
+    let expiry_year = match expiry_year.len() {
+        2 | 4 if is_digits(expiry_year) => expiry_year
+            .parse::<i64>()
+            .ok()
+            .map(|x| if x < 100 { 2000 + x } else { x }),
+        _ => None,
+    };
+    if card.expiry_year.trim().is_empty() {
+        error(Field::ExpiryYear, Reason::Missing);
+    } else if let Some(expiry_year) = expiry_year {
+        if expiry_year < year || (expiry_year == year && expiry_month.is_some_and(|x| x < month)) {
+            error(Field::ExpiryYear, Reason::Expired);
+        }
+    } else {
+        error(Field::ExpiryYear, Reason::Invalid);
+    }
+
+    let cvv = card.cvv.trim();
+    let cvv_lengths = brand.map_or(3..=4, |x| x.cvv_length()..=x.cvv_length());
+    if cvv.is_empty() {
+        error(Field::Cvv, Reason::Missing);
+    } else if !is_digits(cvv) {
+        error(Field::Cvv, Reason::Invalid);
+    } else if !cvv_lengths.contains(&cvv.len()) {
+        error(Field::Cvv, Reason::Length);
+    }
+
+    if card.zip_code.trim().is_empty() {
+        error(Field::ZipCode, Reason::Missing);
+    }
+
+    match brand {
+        Some(brand) if errors.is_empty() => Ok(brand),
+        _ => Err(errors),
+    }
+}
+
+/**
+ * Luhn (mod 10) checksum of a number made of digits only.
+ */
+#[must_use]
+pub fn luhn(number: &str) -> bool {
+    let sum = number
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(n, x)| {
+            let digit = u32::from(x.wrapping_sub(b'0'));
+            if n % 2 == 1 {
+                let double = digit * 2;
+                if double > 9 { double - 9 } else { double }
+            } else {
+                digit
+            }
+        })
+        .sum::<u32>();
+
+    is_digits(number) && sum % 10 == 0
+}
+
+fn digits(number: &str) -> String {
+    number
+        .chars()
+        .filter(|x| !x.is_whitespace() && *x != '-')
+        .collect()
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|x| x.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::card::{Brand, Field, FieldError, Reason};
+
+    fn card(number: &str, cvv: &str) -> crate::NewCard {
+        crate::NewCard {
+            name: "John Doe".to_string(),
+            number: number.to_string().into(),
+            expiry_year: "2030".to_string(),
+            expiry_month: "01".to_string(),
+            cvv: cvv.to_string().into(),
+            zip_code: "75001".to_string(),
+        }
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Brand::detect("4242 4242 4242 4242"), Some(Brand::Visa));
+        assert_eq!(Brand::detect("5555555555554444"), Some(Brand::Mastercard));
+        assert_eq!(Brand::detect("2223003122003222"), Some(Brand::Mastercard));
+        assert_eq!(Brand::detect("378282246310005"), Some(Brand::Amex));
+        assert_eq!(Brand::detect("6759649826438453"), Some(Brand::Maestro));
+        assert_eq!(Brand::detect("6011111111111117"), Some(Brand::Discover));
+        assert_eq!(Brand::detect("4011780000000000"), Some(Brand::Elo));
+        assert_eq!(Brand::detect("6362970000457013"), Some(Brand::Elo));
+        assert_eq!(Brand::detect("6062825624254001"), Some(Brand::Hipercard));
+        assert_eq!(Brand::detect("3530111333300000"), Some(Brand::Jcb));
+        assert_eq!(Brand::detect("1234567890123456"), None);
+    }
+
+    #[test]
+    fn luhn() {
+        assert!(crate::card::luhn("4242424242424242"));
+        assert!(crate::card::luhn("378282246310005"));
+        assert!(!crate::card::luhn("4242424242424241"));
+        assert!(!crate::card::luhn("42424242a4242424"));
+    }
+
+    #[test]
+    fn validate() {
+        let validate = |card| crate::card::validate_at(&card, 2026, 10);
+
+        assert_eq!(
+            validate(card("4242-4242-4242-4242", "123")),
+            Ok(Brand::Visa)
+        );
+        assert_eq!(validate(card("378282246310005", "1234")), Ok(Brand::Amex));
+        assert_eq!(
+            validate(card("378282246310005", "123")),
+            Err(vec![FieldError {
+                field: Field::Cvv,
+                reason: Reason::Length,
+            }])
+        );
+
+        let mut expired = card("4242424242424241", "12");
+        expired.expiry_year = "26".to_string();
+        expired.expiry_month = "09".to_string();
+        expired.zip_code = String::new();
+
+        let errors = validate(expired).unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|x| (x.field, x.reason))
+                .collect::<Vec<_>>(),
+            [
+                (Field::Number, Reason::Checksum),
+                (Field::ExpiryYear, Reason::Expired),
+                (Field::Cvv, Reason::Length),
+                (Field::ZipCode, Reason::Missing),
+            ]
+        );
+
+        let mut invalid = card("1234567890123456", "123");
+        invalid.expiry_month = "13".to_string();
+        let errors = validate(invalid).unwrap_err();
+        assert_eq!(errors[0].reason, Reason::UnknownBrand);
+        assert_eq!(errors[1].field, Field::ExpiryMonth);
+    }
+}
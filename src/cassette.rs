@@ -0,0 +1,374 @@
+/**
+ * Request of an [`Interaction`], without its headers.
+ */
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Request {
+    pub method: String,
+    /** Path and query, without the base URL */
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+impl Request {
+    fn new(request: &crate::middleware::Request) -> Self {
+        let mut body = request.json().ok().flatten();
+        if let Some(body) = &mut body {
+            crate::redact::credentials(body);
+        }
+
+        Self {
+            method: request.method.to_string(),
+            url: path(&request.url).to_string(),
+            body,
+        }
+    }
+}
+
+impl std::fmt::Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+
+        if let Some(body) = &self.body {
+            write!(f, " {body}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Response of an [`Interaction`]. JSON bodies are stored as is, others as a string.
+ */
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Response {
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
+
+impl Response {
+    fn new(response: &crate::middleware::Response) -> Self {
+        let content_type = response
+            .headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .map(ToString::to_string);
+
+        let body = if response.body.trim().is_empty() {
+            serde_json::Value::Null
+        } else {
+            match serde_json::from_str(&response.body) {
+                Ok(mut body) => {
+                    crate::redact::credentials(&mut body);
+                    body
+                }
+                Err(_) => serde_json::Value::String(response.body.clone()),
+            }
+        };
+
+        Self {
+            status: response.status,
+            content_type,
+            body,
+        }
+    }
+
+    fn to_response(&self) -> crate::middleware::Response {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(content_type) = self.content_type.as_deref().and_then(|x| x.parse().ok()) {
+            headers.insert(reqwest::header::CONTENT_TYPE, content_type);
+        }
+
+        let is_json = self
+            .content_type
+            .as_deref()
+            .is_none_or(|x| x.contains("json"));
+        let body = match &self.body {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(body) if !is_json => body.clone(),
+            body => body.to_string(),
+        };
+
+        crate::middleware::Response {
+            status: self.status,
+            headers,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Interaction {
+    pub request: Request,
+    pub response: Response,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /** Sends the requests and writes every interaction to the cassette */
+    Record,
+    /** Answers from the cassette, without sending anything */
+    Replay,
+}
+
+/**
+ * Middleware recording the API traffic to a JSON file, then replaying it.
+ *
+ * Authorization headers are not recorded, and the credentials and card data of the bodies are
+ * replaced; identifiers like payment instrument tokens are kept so the replayed responses are
+ * the recorded ones. The requests are matched on their method, path, query and redacted
+ * body; each interaction is replayed once, in the recorded order, so polling the same URL gets
+ * the successive responses. A request without a matching interaction fails with
+ * [`crate::Error::Unrecorded`].
+ */
+#[derive(Debug)]
+pub struct Cassette {
+    path: std::path::PathBuf,
+    mode: Mode,
+    /** `(interaction, replayed)` */
+    interactions: std::sync::Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl Cassette {
+    /**
+     * Starts an empty cassette, the file is overwritten on the first interaction.
+     */
+    #[must_use]
+    pub fn record(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: Mode::Record,
+            interactions: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn replay(path: impl Into<std::path::PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+        let interactions: Vec<Interaction> =
+            serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&path)?))?;
+
+        Ok(Self {
+            path,
+            mode: Mode::Replay,
+            interactions: std::sync::Mutex::new(
+                interactions.into_iter().map(|x| (x, false)).collect(),
+            ),
+        })
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    #[must_use]
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().iter().map(|(x, _)| x.clone()).collect()
+    }
+
+    /**
+     * Interactions not replayed yet, to check a test sent every recorded request.
+     */
+    #[must_use]
+    pub fn remaining(&self) -> Vec<Interaction> {
+        self.lock()
+            .iter()
+            .filter(|(_, replayed)| !replayed)
+            .map(|(x, _)| x.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Interaction, bool)>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn save(&self, interactions: &[(Interaction, bool)]) -> crate::Result {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let interactions = interactions.iter().map(|(x, _)| x).collect::<Vec<_>>();
+        let mut content = serde_json::to_string_pretty(&interactions)?;
+        content.push('\n');
+        std::fs::write(&self.path, content)?;
+
+        Ok(())
+    }
+}
+
+impl crate::middleware::Middleware for Cassette {
+    fn handle(
+        &self,
+        request: crate::middleware::Request,
+        next: crate::middleware::Next<'_>,
+    ) -> crate::Result<crate::middleware::Response> {
+        let recorded = Request::new(&request);
+
+        match self.mode {
+            Mode::Record => {
+                let response = next.run(request)?;
+
+                let mut interactions = self.lock();
+                interactions.push((
+                    Interaction {
+                        request: recorded,
+                        response: Response::new(&response),
+                    },
+                    false,
+                ));
+                // The response was received, a test shouldn't fail for want of a cassette
+                if let Err(err) = self.save(&interactions) {
+                    log::error!("Failed to save {}: {err}", self.path.display());
+                }
+
+                Ok(response)
+            }
+            Mode::Replay => {
+                let mut interactions = self.lock();
+                let Some((interaction, replayed)) = interactions
+                    .iter_mut()
+                    .find(|(x, replayed)| !replayed && x.request == recorded)
+                else {
+                    log::error!("Unrecorded request in {}: {recorded}", self.path.display());
+
+                    return Err(crate::Error::Unrecorded(format!(
+                        "{recorded} in {}",
+                        self.path.display()
+                    )));
+                };
+                *replayed = true;
+
+                Ok(interaction.response.to_response())
+            }
+        }
+    }
+}
+
+/**
+ * Strips the scheme and host of an URL.
+ */
+fn path(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None => url,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn api(
+        config: crate::Config,
+        cassette: &std::sync::Arc<crate::cassette::Cassette>,
+    ) -> crate::Result<crate::SumUp> {
+        let mut middlewares = crate::middleware::Chain::new();
+        middlewares.push(cassette.clone());
+
+        crate::SumUp::from(crate::Config {
+            middlewares,
+
+            ..config
+        })
+    }
+
+    fn card() -> crate::NewCard {
+        crate::NewCard {
+            name: "John Doe".to_string(),
+            number: "4242424242424242".to_string().into(),
+            expiry_year: "2030".to_string(),
+            expiry_month: "01".to_string(),
+            cvv: "123".to_string().into(),
+            zip_code: "75001".to_string(),
+        }
+    }
+
+    #[test]
+    fn record_replay() -> crate::Result {
+        let path = std::env::temp_dir().join(format!("sumup-cassette-{}.json", std::process::id()));
+
+        let server = crate::mock::Server::start()?;
+        let config = server.config();
+
+        let cassette = std::sync::Arc::new(crate::cassette::Cassette::record(&path));
+        let (instrument, recorded) = {
+            let api = api(config.clone(), &cassette)?;
+            let instrument = api
+                .customer()
+                .create_payment_instruments(crate::mock::CUSTOMER_ID, &card())?;
+
+            let transaction = api
+                .transactions()
+                .find_by_code(crate::mock::TRANSACTION_CODE)?;
+
+            (instrument, transaction)
+        };
+        drop(server);
+
+        let content = std::fs::read_to_string(&path)?;
+        for secret in [
+            crate::mock::CLIENT_SECRET,
+            crate::mock::PASSWORD,
+            "mock-access-token",
+            "4242424242424242",
+            "\"123\"",
+        ] {
+            assert!(!content.contains(secret), "{secret} recorded");
+        }
+
+        let cassette = std::sync::Arc::new(crate::cassette::Cassette::replay(&path)?);
+        let api = api(config, &cassette)?;
+        let replayed = api
+            .customer()
+            .create_payment_instruments(crate::mock::CUSTOMER_ID, &card())?;
+        assert_eq!(replayed.token, instrument.token);
+        let replayed = api
+            .transactions()
+            .find_by_code(crate::mock::TRANSACTION_CODE)?;
+        assert_eq!(replayed.id, recorded.id);
+        assert!(cassette.remaining().is_empty());
+
+        let err = api
+            .transactions()
+            .find_by_code(crate::mock::TRANSACTION_CODE)
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Unrecorded(_)));
+
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn record_unwritable() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+
+        let cassette =
+            std::sync::Arc::new(crate::cassette::Cassette::record("/dev/null/cassette.json"));
+        let api = api(server.config(), &cassette)?;
+
+        api.transactions()
+            .find_by_code(crate::mock::TRANSACTION_CODE)?;
+        assert_eq!(cassette.interactions().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(
+            super::path("https://api.sumup.com/v0.1/me?format=json"),
+            "/v0.1/me?format=json"
+        );
+        assert_eq!(super::path("http://127.0.0.1:8080"), "/");
+        assert_eq!(super::path("/token"), "/token");
+    }
+}
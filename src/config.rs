@@ -1,4 +1,4 @@
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Default)]
 pub struct Config {
     /**
      * This is the client id that you receive after you
@@ -31,6 +31,37 @@ pub struct Config {
     pub access_token: Option<String>,
     /** This is the refresh token through which can be requested new access token */
     pub refresh_token: Option<String>,
+    /**
+     * This is the root of the API URLs, `https://api.sumup.com` by default. Point it to a
+     * `mock::Server` to test without network
+     */
+    pub base_url: Option<String>,
+    /** This is the list of middlewares wrapping every request, the token requests included */
+    pub middlewares: crate::middleware::Chain,
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &crate::redact::REDACTED)
+            .field("grant_type", &self.grant_type)
+            .field("scopes", &self.scopes)
+            .field("code", &crate::redact::option(self.code.as_ref()))
+            .field("username", &self.username)
+            .field("password", &crate::redact::option(self.password.as_ref()))
+            .field(
+                "access_token",
+                &crate::redact::option(self.access_token.as_ref()),
+            )
+            .field(
+                "refresh_token",
+                &crate::redact::option(self.refresh_token.as_ref()),
+            )
+            .field("base_url", &self.base_url)
+            .field("middlewares", &self.middlewares)
+            .finish()
+    }
 }
 
 impl Config {
@@ -0,0 +1,136 @@
+/**
+ * Minimal UTC calendar date, the API only exchanges dates as ISO 8601 strings.
+ */
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /**
+     * Parses the date part of `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS…` or `YYMMDD` strings.
+     */
+    pub fn parse(s: &str) -> Option<Self> {
+        let date = if s.len() >= 10 && s.as_bytes()[4] == b'-' {
+            Self {
+                year: s.get(0..4)?.parse().ok()?,
+                month: s.get(5..7)?.parse().ok()?,
+                day: s.get(8..10)?.parse().ok()?,
+            }
+        } else {
+            Self {
+                year: 2000 + s.get(0..2)?.parse::<i64>().ok()?,
+                month: s.get(2..4)?.parse().ok()?,
+                day: s.get(4..6)?.parse().ok()?,
+            }
+        };
+
+        if (1..=12).contains(&date.month)
+            && (1..=days_in_month(date.year, date.month)).contains(&date.day)
+        {
+            Some(date)
+        } else {
+            None
+        }
+    }
+
+    /** Days since 1970-01-01 */
+    pub fn days(self) -> i64 {
+        let year = if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let month = i64::from(self.month);
+        let mp = if month > 2 { month - 3 } else { month + 9 };
+        let doy = (153 * mp + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+        era * 146_097 + doe - 719_468
+    }
+
+    /** Converts days since 1970-01-01 */
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/** Seconds since the UNIX epoch */
+pub(crate) fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/** Current UTC time formatted as `YYYY-MM-DDTHH:MM:SSZ` */
+pub(crate) fn now_rfc3339() -> String {
+    let now = now();
+    let date = Date::from_days(now.div_euclid(86_400));
+    let seconds = now.rem_euclid(86_400);
+
+    format!(
+        "{date}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn parse() {
+        let date = super::Date::parse("2021-01-05T10:29:31.000Z").unwrap();
+
+        assert_eq!(date.days(), 18_632);
+        assert_eq!(super::Date::parse("210105"), Some(date));
+        assert_eq!(super::Date::parse("211305"), None);
+        assert_eq!(super::Date::parse("2021-02-29"), None);
+        assert_eq!(super::Date::parse("2021-04-31"), None);
+        assert_eq!(super::Date::parse("1900-02-29"), None);
+        assert!(super::Date::parse("2024-02-29").is_some());
+        assert!(super::Date::parse("2000-02-29").is_some());
+    }
+
+    #[test]
+    fn from_days() {
+        let date = super::Date::from_days(18_632);
+
+        assert_eq!(date.to_string(), "2021-01-05");
+        assert_eq!(super::Date::from_days(-1).to_string(), "1969-12-31");
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[derive(Clone, Default, Eq, PartialEq, serde::Deserialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
 pub struct AccessToken {
     pub access_token: String,
@@ -16,6 +16,21 @@ impl AccessToken {
     }
 }
 
+impl std::fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("access_token", &crate::redact::REDACTED)
+            .field("token_type", &self.token_type)
+            .field("expires_in", &self.expires_in)
+            .field("scope", &self.scope)
+            .field(
+                "refresh_token",
+                &crate::redact::option(self.refresh_token.as_ref()),
+            )
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Default, serde::Deserialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
 pub struct Account {
@@ -51,6 +66,8 @@ pub struct PersonalProfile {
 
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Checkout {
+    #[serde(default, skip_serializing)]
+    pub id: Option<String>,
     pub amount: f32,
     pub currency: String,
     pub checkout_reference: String,
@@ -63,6 +80,14 @@ pub struct Checkout {
     pub pay_from_email: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub return_url: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub transaction_id: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub transaction_code: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
@@ -177,17 +202,81 @@ pub struct Settings {
     pub operator_personal_profile_note: bool,
 }
 
-#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+/**
+ * Card details sent to create a payment instrument.
+ *
+ * The number and the CVV are wiped from memory when they are dropped and never appear in
+ * `Debug` output.
+ */
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
-pub struct Card {
+pub struct NewCard {
     pub name: String,
-    pub number: String,
+    pub number: zeroize::Zeroizing<String>,
     pub expiry_year: String,
     pub expiry_month: String,
-    pub cvv: String,
+    pub cvv: zeroize::Zeroizing<String>,
     pub zip_code: String,
 }
 
+impl NewCard {
+    /**
+     * See [`crate::card::validate`].
+     */
+    pub fn validate(&self) -> Result<crate::card::Brand, Vec<crate::card::FieldError>> {
+        crate::card::validate(self)
+    }
+}
+
+impl std::fmt::Debug for NewCard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NewCard")
+            .field("name", &self.name)
+            .field("number", &crate::redact::card_number(&self.number))
+            .field("expiry_year", &self.expiry_year)
+            .field("expiry_month", &self.expiry_month)
+            .field("cvv", &crate::redact::REDACTED)
+            .field("zip_code", &self.zip_code)
+            .finish()
+    }
+}
+
+#[deprecated(note = "use `NewCard` to create instruments and `PaymentInstrument` to read them")]
+pub type Card = NewCard;
+
+/**
+ * Tokenized card saved for a customer.
+ */
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct PaymentInstrument {
+    /** Token to pay a checkout with or to deactivate the instrument */
+    pub token: String,
+    pub active: bool,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub card: MaskedCard,
+    pub mandate: Option<Mandate>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct MaskedCard {
+    pub last_4_digits: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct Mandate {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub status: String,
+    pub merchant_code: String,
+}
+
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
 pub struct Payout {
@@ -243,6 +332,70 @@ pub struct Transaction {
     pub tax_enabled: bool,
 }
 
+impl Transaction {
+    /**
+     * Refund state derived from `events`, or `transaction_events` for transactions without
+     * detailed events.
+     *
+     * Failed refunds are ignored, pending ones are already counted as refunded. Nothing is
+     * refundable once the transaction is charged back, cancelled or failed.
+     */
+    #[must_use]
+    pub fn refundable(&self) -> Refundable {
+        let mut refundable = Refundable::default();
+
+        let events = self
+            .events
+            .iter()
+            .filter(|x| x.transaction_id == self.id)
+            .map(|x| (x.ty.as_str(), x.status.as_str(), x.amount));
+        let transaction_events = self
+            .transaction_events
+            .iter()
+            .map(|x| (x.event_type.as_str(), x.status.as_str(), x.amount));
+
+        let events = if self.events.is_empty() {
+            transaction_events.collect::<Vec<_>>()
+        } else {
+            events.collect::<Vec<_>>()
+        };
+
+        for (ty, status, amount) in events {
+            if status == "FAILED" {
+                continue;
+            }
+
+            match ty {
+                "REFUND" => refundable.refunded += amount.abs(),
+                "CHARGE_BACK" => refundable.charged_back = true,
+                _ => (),
+            }
+        }
+
+        match self.status {
+            Status::ChargeBack => refundable.charged_back = true,
+            Status::Refunded if refundable.refunded == 0. => refundable.refunded = self.amount,
+            _ => (),
+        }
+
+        if !refundable.charged_back && matches!(self.status, Status::Successful | Status::Refunded)
+        {
+            refundable.amount = (self.amount - refundable.refunded).max(0.);
+        }
+
+        refundable
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Refundable {
+    /** Amount which can still be refunded */
+    pub amount: f32,
+    /** Sum of the refunds, pending ones included */
+    pub refunded: f32,
+    pub charged_back: bool,
+}
+
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
 pub struct TransactionCard {
@@ -320,6 +473,7 @@ pub enum Status {
     Cancelled,
     Failed,
     Refunded,
+    #[serde(rename = "CHARGE_BACK")]
     ChargeBack,
 }
 
@@ -1,19 +1,224 @@
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
+/**
+ * Variants depend on the enabled features, match with a wildcard arm.
+ */
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("{0:?}")]
-    Api(Response),
+    #[error("{0}")]
+    Api(Box<ApiError>),
     #[error("{0}")]
     Auth(&'static str),
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("Empty response (HTTP {status})")]
+    EmptyResponse { status: u16 },
     #[error("{0:?}")]
     Http(#[from] reqwest::Error),
+    #[error("Invalid card: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    InvalidCard(Vec<crate::card::FieldError>),
     #[error("Invalid scope: {0}")]
     InvalidScope(String),
+    #[error("Invalid statement: {0}")]
+    InvalidStatement(String),
+    #[error("Invalid webhook: {0}")]
+    InvalidWebhook(String),
     #[error("{0}")]
     Io(#[from] std::io::Error),
     #[error("{0}")]
     Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Middleware(Box<dyn std::error::Error + Send + Sync>),
+    #[error("QR code of {length} bytes exceeds the capacity of {capacity} bytes")]
+    QrCodeTooLong { length: usize, capacity: usize },
+    #[error("Refund of {requested:.2} exceeds the refundable amount {refundable:.2}")]
+    RefundExceeded { requested: f32, refundable: f32 },
+    #[cfg(feature = "sqlite")]
+    #[error("{0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Timeout: {0}")]
+    Timeout(String),
+    #[error("Unexpected response (HTTP {status}, {}): {body}", content_type.as_deref().unwrap_or("no content type"))]
+    UnexpectedResponse {
+        status: u16,
+        content_type: Option<String>,
+        /** Truncated body */
+        body: String,
+    },
+    #[error("Unrecorded request: {0}")]
+    Unrecorded(String),
+}
+
+impl Error {
+    #[must_use]
+    pub fn api(&self) -> Option<&ApiError> {
+        match self {
+            Self::Api(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        self.api().is_some_and(ApiError::is_not_found)
+    }
+
+    #[must_use]
+    pub fn is_auth(&self) -> bool {
+        self.api().is_some_and(ApiError::is_auth)
+    }
+
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        self.api().is_some_and(ApiError::is_rate_limited)
+    }
+
+    /**
+     * Server errors, rate limiting and network failures.
+     */
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Api(err) => err.is_retryable(),
+            Self::EmptyResponse { status } | Self::UnexpectedResponse { status, .. } => {
+                is_retryable_status(*status)
+            }
+            Self::Http(err) => err.is_timeout() || err.is_connect(),
+            _ => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_validation(&self) -> bool {
+        matches!(self, Self::InvalidCard(_)) || self.api().is_some_and(ApiError::is_validation)
+    }
+
+    /** Code of the first error message */
+    #[must_use]
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.api().and_then(ApiError::code)
+    }
+
+    /** Parameter of the first message pointing one, for validation errors */
+    #[must_use]
+    pub fn param(&self) -> Option<&str> {
+        self.api().and_then(ApiError::param)
+    }
+}
+
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/** Headers which may carry the id of the request in SumUp logs */
+const REQUEST_ID_HEADERS: &[&str] = &[
+    "x-request-id",
+    "request-id",
+    "x-correlation-id",
+    "traceparent",
+];
+
+/**
+ * Error response of the API.
+ */
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    pub request_id: Option<String>,
+    pub response: Response,
+    pub body: String,
+}
+
+impl ApiError {
+    pub(crate) fn new(
+        status: u16,
+        headers: &reqwest::header::HeaderMap,
+        body: String,
+    ) -> Result<Self> {
+        let request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|x| headers.get(*x))
+            .and_then(|x| x.to_str().ok())
+            .map(ToString::to_string);
+
+        Ok(Self {
+            status,
+            request_id,
+            response: serde_json::from_str(&body)?,
+            body,
+        })
+    }
+
+    #[must_use]
+    pub fn messages(&self) -> Vec<&Message> {
+        match &self.response {
+            Response::Message(message) => vec![message],
+            Response::Messages(messages) => messages.iter().collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.messages().into_iter().find_map(Message::code)
+    }
+
+    #[must_use]
+    pub fn param(&self) -> Option<&str> {
+        self.messages().into_iter().find_map(Message::param)
+    }
+
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        self.status == 404 || self.code() == Some(ErrorCode::NotFound)
+    }
+
+    #[must_use]
+    pub fn is_auth(&self) -> bool {
+        matches!(self.status, 401 | 403)
+            || matches!(
+                self.code(),
+                Some(
+                    ErrorCode::NotAuthorized
+                        | ErrorCode::InvalidAccessToken
+                        | ErrorCode::InvalidGrant
+                        | ErrorCode::InvalidClient
+                )
+            )
+    }
+
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        is_retryable_status(self.status)
+    }
+
+    #[must_use]
+    pub fn is_validation(&self) -> bool {
+        matches!(self.status, 400 | 422)
+            || matches!(self.code(), Some(ErrorCode::Invalid | ErrorCode::Missing))
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP {}", self.status)?;
+
+        for (n, message) in self.messages().iter().enumerate() {
+            let separator = if n == 0 { ": " } else { "; " };
+            write!(f, "{separator}{message}")?;
+        }
+
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request id {request_id})")?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -36,12 +241,212 @@ pub enum Message {
         #[serde(alias = "error_message")]
         message: String,
         instance: Option<String>,
-        error_code: Option<String>,
+        error_code: Option<ErrorCode>,
         param: Option<String>,
         #[serde(rename = "type")]
         ty: Option<String>,
         title: Option<String>,
-        status: Option<u8>,
+        status: Option<u16>,
         detail: Option<String>,
     },
 }
+
+impl Message {
+    #[must_use]
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Short { message, .. } | Self::Long { message, .. } => message,
+        }
+    }
+
+    /**
+     * `error_code`, or the OAuth `error` of short messages.
+     */
+    #[must_use]
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Short { message, .. } => Some(ErrorCode::from(message.as_str())),
+            Self::Long { error_code, .. } => error_code.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn param(&self) -> Option<&str> {
+        match self {
+            Self::Short { .. } => None,
+            Self::Long { param, .. } => param.as_deref(),
+        }
+    }
+}
+
+/**
+ * Documented error codes, OAuth ones are lower case.
+ */
+#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(from = "String")]
+pub enum ErrorCode {
+    CheckoutProcessed,
+    DuplicatedCheckout,
+    Forbidden,
+    Invalid,
+    InvalidAccessToken,
+    InvalidClient,
+    InvalidGrant,
+    InvalidRequest,
+    InvalidScope,
+    Missing,
+    NotAuthorized,
+    NotFound,
+    UnauthorizedClient,
+    UnsupportedGrantType,
+    Unknown(String),
+}
+
+impl From<&str> for ErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "CHECKOUT_PROCESSED" => Self::CheckoutProcessed,
+            "DUPLICATED_CHECKOUT" => Self::DuplicatedCheckout,
+            "FORBIDDEN" => Self::Forbidden,
+            "INVALID" => Self::Invalid,
+            "INVALID_ACCESS_TOKEN" => Self::InvalidAccessToken,
+            "invalid_client" => Self::InvalidClient,
+            "invalid_grant" => Self::InvalidGrant,
+            "invalid_request" => Self::InvalidRequest,
+            "invalid_scope" => Self::InvalidScope,
+            "MISSING" => Self::Missing,
+            "NOT_AUTHORIZED" => Self::NotAuthorized,
+            "NOT_FOUND" => Self::NotFound,
+            "unauthorized_client" => Self::UnauthorizedClient,
+            "unsupported_grant_type" => Self::UnsupportedGrantType,
+            code => Self::Unknown(code.to_string()),
+        }
+    }
+}
+
+impl From<String> for ErrorCode {
+    fn from(code: String) -> Self {
+        Self::from(code.as_str())
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::CheckoutProcessed => "CHECKOUT_PROCESSED",
+            Self::DuplicatedCheckout => "DUPLICATED_CHECKOUT",
+            Self::Forbidden => "FORBIDDEN",
+            Self::Invalid => "INVALID",
+            Self::InvalidAccessToken => "INVALID_ACCESS_TOKEN",
+            Self::InvalidClient => "invalid_client",
+            Self::InvalidGrant => "invalid_grant",
+            Self::InvalidRequest => "invalid_request",
+            Self::InvalidScope => "invalid_scope",
+            Self::Missing => "MISSING",
+            Self::NotAuthorized => "NOT_AUTHORIZED",
+            Self::NotFound => "NOT_FOUND",
+            Self::UnauthorizedClient => "unauthorized_client",
+            Self::UnsupportedGrantType => "unsupported_grant_type",
+            Self::Unknown(code) => code,
+        };
+
+        f.write_str(s)
+    }
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Short {
+                message,
+                description,
+            } => write!(f, "{message} ({description})"),
+            Self::Long {
+                message,
+                error_code,
+                param,
+                ..
+            } => {
+                f.write_str(message)?;
+                if let Some(error_code) = error_code {
+                    write!(f, " [{error_code}]")?;
+                }
+                if let Some(param) = param {
+                    write!(f, " ({param})")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn error(status: u16, body: &str) -> crate::ApiError {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-request-id", "0a1b2c".parse().unwrap());
+
+        crate::ApiError::new(status, &headers, body.to_string()).unwrap()
+    }
+
+    #[test]
+    fn api_error() {
+        let err = error(
+            404,
+            r#"{"type": "https://developer.sumup.com/docs/problem/not-found/", "title": "Not Found", "status": 404, "detail": "Checkout not found", "message": "Resource not found", "error_code": "NOT_FOUND"}"#,
+        );
+
+        assert!(err.is_not_found());
+        assert!(!err.is_retryable());
+        assert_eq!(err.request_id.as_deref(), Some("0a1b2c"));
+        assert_eq!(
+            err.to_string(),
+            "HTTP 404: Resource not found [NOT_FOUND] (request id 0a1b2c)"
+        );
+
+        let err = crate::Error::Api(Box::new(error(
+            400,
+            r#"[{"message": "Validation error", "error_code": "MISSING", "param": "amount"}, {"message": "Validation error", "error_code": "INVALID", "param": "currency"}]"#,
+        )));
+
+        assert!(err.is_validation());
+        assert_eq!(err.code(), Some(crate::ErrorCode::Missing));
+        assert_eq!(err.param(), Some("amount"));
+        assert_eq!(
+            err.to_string(),
+            "HTTP 400: Validation error [MISSING] (amount); Validation error [INVALID] (currency) (request id 0a1b2c)"
+        );
+    }
+
+    #[test]
+    fn retryable() {
+        let err = error(
+            503,
+            r#"{"error": "unavailable", "error_description": "Maintenance"}"#,
+        );
+
+        assert!(err.is_retryable());
+        assert_eq!(err.messages()[0].message(), "unavailable");
+    }
+
+    #[test]
+    fn error_code() {
+        let err = error(
+            401,
+            r#"{"error": "invalid_grant", "error_description": "Bad credentials"}"#,
+        );
+        assert_eq!(err.code(), Some(crate::ErrorCode::InvalidGrant));
+        assert!(err.is_auth());
+
+        let err = error(
+            409,
+            r#"{"message": "Checkout already processed", "error_code": "CHECKOUT_PROCESSED"}"#,
+        );
+        assert_eq!(err.code(), Some(crate::ErrorCode::CheckoutProcessed));
+
+        let code = crate::ErrorCode::from("SOMETHING_NEW");
+        assert_eq!(code, crate::ErrorCode::Unknown("SOMETHING_NEW".to_string()));
+        assert_eq!(code.to_string(), "SOMETHING_NEW");
+    }
+}
@@ -0,0 +1,363 @@
+const ESC: u8 = 0x1b;
+const GS: u8 = 0x1d;
+const LF: u8 = 0x0a;
+
+/** Characters of the PC437 code page from 0x80 to 0xFF, the bytes below are ASCII */
+const PC437: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐\
+    └┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+/** Bytes held by a model 2 QR code with error correction level M, version 40 */
+pub const QR_CODE_CAPACITY: usize = 2_331;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Paper {
+    #[default]
+    Mm58,
+    Mm80,
+}
+
+impl Paper {
+    /** Number of characters per line with the default font A */
+    #[must_use]
+    pub fn columns(self) -> usize {
+        match self {
+            Self::Mm58 => 32,
+            Self::Mm80 => 48,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub paper: Paper,
+    /** Printed in bold at the top of the receipt, usually the business name */
+    pub header: Option<String>,
+    /** Prints the transaction code as a QR code at the bottom of the receipt */
+    pub qr_code: bool,
+    /** Sends the paper cut command at the end of the receipt */
+    pub cut: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            paper: Paper::default(),
+            header: None,
+            qr_code: false,
+            cut: true,
+        }
+    }
+}
+
+/**
+ * Low level ESC/POS command builder.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Commands {
+    bytes: Vec<u8>,
+}
+
+impl Commands {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /** ESC @, then ESC t 0 to select the PC437 code page [`Self::text`] encodes to */
+    pub fn init(&mut self) -> &mut Self {
+        self.raw(&[ESC, b'@', ESC, b't', 0])
+    }
+
+    /** ESC a */
+    pub fn align(&mut self, align: Align) -> &mut Self {
+        let n = match align {
+            Align::Left => 0,
+            Align::Center => 1,
+            Align::Right => 2,
+        };
+
+        self.raw(&[ESC, b'a', n])
+    }
+
+    /** ESC E */
+    pub fn bold(&mut self, enabled: bool) -> &mut Self {
+        self.raw(&[ESC, b'E', u8::from(enabled)])
+    }
+
+    /** GS ! */
+    pub fn double_size(&mut self, enabled: bool) -> &mut Self {
+        self.raw(&[GS, b'!', if enabled { 0x11 } else { 0x00 }])
+    }
+
+    /**
+     * Encoded to PC437, characters outside of the code page are printed as `?`.
+     */
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        let bytes = text
+            .chars()
+            .map(|c| match PC437.chars().position(|x| x == c) {
+                _ if c.is_ascii() => c as u8,
+                Some(n) => 0x80 + n as u8,
+                None => b'?',
+            })
+            .collect::<Vec<_>>();
+
+        self.raw(&bytes)
+    }
+
+    pub fn line(&mut self, text: &str) -> &mut Self {
+        self.text(text).raw(&[LF])
+    }
+
+    /** ESC d */
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        self.raw(&[ESC, b'd', lines])
+    }
+
+    /**
+     * GS ( k, model 2 with error correction level M. The data is stored as UTF-8 bytes, up to
+     * [`QR_CODE_CAPACITY`].
+     */
+    pub fn qr_code(&mut self, data: &str, module_size: u8) -> crate::Result<&mut Self> {
+        let data = data.as_bytes();
+        if data.len() > QR_CODE_CAPACITY {
+            return Err(crate::Error::QrCodeTooLong {
+                length: data.len(),
+                capacity: QR_CODE_CAPACITY,
+            });
+        }
+
+        // Fits in u16 once checked against the capacity
+        let [pl, ph] = ((data.len() + 3) as u16).to_le_bytes();
+
+        Ok(self
+            .raw(&[GS, b'(', b'k', 4, 0, 49, 65, 50, 0])
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 67, module_size])
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 69, 49])
+            .raw(&[GS, b'(', b'k', pl, ph, 49, 80, 48])
+            .raw(data)
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 81, 48]))
+    }
+
+    /** GS V, full cut */
+    pub fn cut(&mut self) -> &mut Self {
+        self.raw(&[GS, b'V', 0])
+    }
+
+    pub fn raw(&mut self, bytes: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+}
+
+/**
+ * Renders receipts as ESC/POS byte streams for thermal printers.
+ *
+ * Text is sent in the printer default code page (PC437), characters outside of ASCII are replaced
+ * by `?`.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Renderer {
+    options: Options,
+}
+
+impl Renderer {
+    #[must_use]
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+
+    #[must_use]
+    pub fn receipt(&self, receipt: &crate::Receipt) -> Vec<u8> {
+        self.transaction(&receipt.transaction_data)
+    }
+
+    #[must_use]
+    pub fn transaction(&self, transaction: &crate::Transaction) -> Vec<u8> {
+        let width = self.options.paper.columns();
+        let separator = "-".repeat(width);
+        let currency = &transaction.currency;
+
+        let mut commands = Commands::new();
+        commands.init();
+
+        commands.align(Align::Center);
+        if let Some(header) = &self.options.header {
+            commands
+                .bold(true)
+                .double_size(true)
+                .line(header)
+                .double_size(false)
+                .bold(false);
+        }
+        commands
+            .line(&transaction.local_time)
+            .line(&transaction.transaction_code);
+
+        commands.align(Align::Left).line(&separator);
+
+        for product in &transaction.products {
+            commands.line(&product.name).line(&columns(
+                &format!("  {} x {:.2}", product.quantity, product.price_with_vat),
+                &format!("{:.2}", product.total_with_vat),
+                width,
+            ));
+        }
+
+        if !transaction.products.is_empty() {
+            commands.line(&separator);
+        }
+
+        if transaction.tip_amount > 0. {
+            commands.line(&columns(
+                "Tip",
+                &format!("{:.2}", transaction.tip_amount),
+                width,
+            ));
+        }
+
+        commands
+            .bold(true)
+            .line(&columns(
+                "TOTAL",
+                &format!("{:.2} {currency}", transaction.amount),
+                width,
+            ))
+            .bold(false);
+
+        if transaction.tax_enabled {
+            commands.line(&columns(
+                "VAT",
+                &format!("{:.2} {currency}", transaction.vat_amount),
+                width,
+            ));
+        }
+
+        commands.line(&separator).line(&columns(
+            &transaction.payment_type.to_string(),
+            &transaction.status.to_string(),
+            width,
+        ));
+
+        if !transaction.card.last_4_digits.is_empty() {
+            commands.line(&columns(
+                &transaction.card.ty,
+                &format!("**** {}", transaction.card.last_4_digits),
+                width,
+            ));
+        }
+
+        if self.options.qr_code {
+            commands.feed(1).align(Align::Center);
+            if let Err(err) = commands.qr_code(&transaction.transaction_code, 6) {
+                log::warn!("Receipt printed without QR code: {err}");
+            }
+            commands.align(Align::Left);
+        }
+
+        commands.feed(3);
+
+        if self.options.cut {
+            commands.cut();
+        }
+
+        commands.build()
+    }
+}
+
+/**
+ * Left and right aligned text on the same line, the left part is truncated if both don't fit.
+ */
+fn columns(left: &str, right: &str, width: usize) -> String {
+    let right_len = right.chars().count();
+    let available = width.saturating_sub(right_len + 1);
+    let left = left.chars().take(available).collect::<String>();
+    let padding = width.saturating_sub(left.chars().count() + right_len);
+
+    format!("{left}{}{right}", " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod test {
+    fn transaction() -> crate::Transaction {
+        serde_json::from_str(include_str!("../fixtures/transaction.json")).unwrap()
+    }
+
+    #[test]
+    fn transaction_58mm() {
+        let renderer = crate::escpos::Renderer::new(crate::escpos::Options {
+            header: Some("Café de Flore".to_string()),
+            qr_code: true,
+
+            ..Default::default()
+        });
+
+        assert_eq!(
+            renderer.transaction(&transaction()),
+            include_bytes!("../fixtures/escpos/transaction-58mm.bin")
+        );
+    }
+
+    #[test]
+    fn transaction_80mm() {
+        let renderer = crate::escpos::Renderer::new(crate::escpos::Options {
+            paper: crate::escpos::Paper::Mm80,
+            cut: false,
+
+            ..Default::default()
+        });
+
+        assert_eq!(
+            renderer.transaction(&transaction()),
+            include_bytes!("../fixtures/escpos/transaction-80mm.bin")
+        );
+    }
+
+    #[test]
+    fn qr_code() -> crate::Result {
+        let bytes = crate::escpos::Commands::new().qr_code("café", 6)?.build();
+        let store = [0x1d, b'(', b'k', 8, 0, 49, 80, 48];
+        let start = bytes.windows(8).position(|x| x == store).unwrap() + 8;
+        assert_eq!(&bytes[start..start + 5], "café".as_bytes());
+
+        let data = "x".repeat(crate::escpos::QR_CODE_CAPACITY + 1);
+        assert!(matches!(
+            crate::escpos::Commands::new().qr_code(&data, 6),
+            Err(crate::Error::QrCodeTooLong { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(super::PC437.chars().count(), 128);
+
+        let bytes = crate::escpos::Commands::new().text("é ü ß £ ½ €").build();
+        assert_eq!(
+            bytes,
+            [
+                0x82, b' ', 0x81, b' ', 0xe1, b' ', 0x9c, b' ', 0xab, b' ', b'?'
+            ]
+        );
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(super::columns("TOTAL", "9.76", 12), "TOTAL   9.76");
+        assert_eq!(super::columns("Very long name", "9.76", 12), "Very lo 9.76");
+    }
+}
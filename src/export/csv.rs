@@ -0,0 +1,515 @@
+/**
+ * Languages writing decimals with a comma, spreadsheets configured for them expect `;` as
+ * delimiter.
+ */
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "bg", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "it", "lt", "lv", "nb", "nl",
+    "no", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "tr", "uk",
+];
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub delimiter: char,
+    pub decimal_separator: char,
+    /** Writes the columns name as first row */
+    pub header: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            decimal_separator: '.',
+            header: true,
+        }
+    }
+}
+
+impl Options {
+    /**
+     * Options matching a locale like the merchant profile one (`de-DE`, `fr_FR`, `en`…).
+     */
+    #[must_use]
+    pub fn locale(locale: &str) -> Self {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if DECIMAL_COMMA_LANGUAGES.contains(&language.as_str()) {
+            Self {
+                delimiter: ';',
+                decimal_separator: ',',
+
+                ..Default::default()
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionColumn {
+    Id,
+    TransactionCode,
+    Timestamp,
+    LocalTime,
+    Status,
+    PaymentType,
+    EntryMode,
+    Amount,
+    Currency,
+    VatAmount,
+    TipAmount,
+    Username,
+    ProductSummary,
+    CardType,
+    CardLast4Digits,
+    /** Product columns are empty unless the writer exports one row per product */
+    ProductName,
+    ProductQuantity,
+    ProductPrice,
+    ProductVatRate,
+    ProductVatAmount,
+    ProductTotal,
+}
+
+impl TransactionColumn {
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Id,
+            Self::TransactionCode,
+            Self::Timestamp,
+            Self::LocalTime,
+            Self::Status,
+            Self::PaymentType,
+            Self::EntryMode,
+            Self::Amount,
+            Self::Currency,
+            Self::VatAmount,
+            Self::TipAmount,
+            Self::Username,
+            Self::ProductSummary,
+            Self::CardType,
+            Self::CardLast4Digits,
+        ]
+    }
+
+    #[must_use]
+    pub fn with_products() -> Vec<Self> {
+        let mut columns = Self::all();
+        columns.extend([
+            Self::ProductName,
+            Self::ProductQuantity,
+            Self::ProductPrice,
+            Self::ProductVatRate,
+            Self::ProductVatAmount,
+            Self::ProductTotal,
+        ]);
+
+        columns
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::TransactionCode => "transaction_code",
+            Self::Timestamp => "timestamp",
+            Self::LocalTime => "local_time",
+            Self::Status => "status",
+            Self::PaymentType => "payment_type",
+            Self::EntryMode => "entry_mode",
+            Self::Amount => "amount",
+            Self::Currency => "currency",
+            Self::VatAmount => "vat_amount",
+            Self::TipAmount => "tip_amount",
+            Self::Username => "username",
+            Self::ProductSummary => "product_summary",
+            Self::CardType => "card_type",
+            Self::CardLast4Digits => "card_last_4_digits",
+            Self::ProductName => "product_name",
+            Self::ProductQuantity => "product_quantity",
+            Self::ProductPrice => "product_price",
+            Self::ProductVatRate => "product_vat_rate",
+            Self::ProductVatAmount => "product_vat_amount",
+            Self::ProductTotal => "product_total",
+        }
+    }
+
+    fn value(self, transaction: &crate::Transaction, product: Option<&crate::Product>) -> Value {
+        match self {
+            Self::Id => Value::text(&transaction.id),
+            Self::TransactionCode => Value::text(&transaction.transaction_code),
+            Self::Timestamp => Value::text(&transaction.timestamp),
+            Self::LocalTime => Value::text(&transaction.local_time),
+            Self::Status => Value::Text(transaction.status.to_string()),
+            Self::PaymentType => Value::Text(transaction.payment_type.to_string()),
+            Self::EntryMode => Value::text(&transaction.entry_mode),
+            Self::Amount => Value::Amount(transaction.amount),
+            Self::Currency => Value::text(&transaction.currency),
+            Self::VatAmount => Value::Amount(transaction.vat_amount),
+            Self::TipAmount => Value::Amount(transaction.tip_amount),
+            Self::Username => Value::text(&transaction.username),
+            Self::ProductSummary => Value::text(&transaction.product_summary),
+            Self::CardType => Value::text(&transaction.card.ty),
+            Self::CardLast4Digits => Value::text(&transaction.card.last_4_digits),
+            Self::ProductName => product.map_or(Value::Empty, |x| Value::text(&x.name)),
+            Self::ProductQuantity => product.map_or(Value::Empty, |x| Value::Number(x.quantity)),
+            Self::ProductPrice => product.map_or(Value::Empty, |x| Value::Amount(x.price)),
+            Self::ProductVatRate => product.map_or(Value::Empty, |x| Value::Number(x.vat_rate)),
+            Self::ProductVatAmount => product.map_or(Value::Empty, |x| Value::Amount(x.vat_amount)),
+            Self::ProductTotal => product.map_or(Value::Empty, |x| Value::Amount(x.total_with_vat)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PayoutColumn {
+    Id,
+    Date,
+    Type,
+    Status,
+    Amount,
+    Fee,
+    Currency,
+    Reference,
+    TransactionCode,
+}
+
+impl PayoutColumn {
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Id,
+            Self::Date,
+            Self::Type,
+            Self::Status,
+            Self::Amount,
+            Self::Fee,
+            Self::Currency,
+            Self::Reference,
+            Self::TransactionCode,
+        ]
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Date => "date",
+            Self::Type => "type",
+            Self::Status => "status",
+            Self::Amount => "amount",
+            Self::Fee => "fee",
+            Self::Currency => "currency",
+            Self::Reference => "reference",
+            Self::TransactionCode => "transaction_code",
+        }
+    }
+
+    fn value(self, payout: &crate::Payout) -> Value {
+        match self {
+            Self::Id => Value::Text(payout.id.to_string()),
+            Self::Date => Value::text(&payout.date),
+            Self::Type => Value::text(&payout.ty),
+            Self::Status => Value::text(&payout.status),
+            Self::Amount => Value::Amount(payout.amount),
+            Self::Fee => Value::Amount(payout.fee),
+            Self::Currency => Value::text(&payout.currency),
+            Self::Reference => Value::text(&payout.reference),
+            Self::TransactionCode => Value::text(&payout.transaction_code),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventColumn {
+    Id,
+    TransactionId,
+    Type,
+    Status,
+    Timestamp,
+    Amount,
+    FeeAmount,
+    InstallmentNumber,
+    DeductedAmount,
+    DeductedFeeAmount,
+}
+
+impl EventColumn {
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Id,
+            Self::TransactionId,
+            Self::Type,
+            Self::Status,
+            Self::Timestamp,
+            Self::Amount,
+            Self::FeeAmount,
+            Self::InstallmentNumber,
+            Self::DeductedAmount,
+            Self::DeductedFeeAmount,
+        ]
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::TransactionId => "transaction_id",
+            Self::Type => "type",
+            Self::Status => "status",
+            Self::Timestamp => "timestamp",
+            Self::Amount => "amount",
+            Self::FeeAmount => "fee_amount",
+            Self::InstallmentNumber => "installment_number",
+            Self::DeductedAmount => "deducted_amount",
+            Self::DeductedFeeAmount => "deducted_fee_amount",
+        }
+    }
+
+    fn value(self, event: &crate::Event) -> Value {
+        match self {
+            Self::Id => Value::Text(event.id.to_string()),
+            Self::TransactionId => Value::text(&event.transaction_id),
+            Self::Type => Value::text(&event.ty),
+            Self::Status => Value::text(&event.status),
+            Self::Timestamp => Value::text(&event.timestamp),
+            Self::Amount => Value::Amount(event.amount),
+            Self::FeeAmount => Value::Amount(event.fee_amount),
+            Self::InstallmentNumber => Value::Text(event.installment_number.to_string()),
+            Self::DeductedAmount => Value::Amount(event.deducted_amount),
+            Self::DeductedFeeAmount => Value::Amount(event.deducted_fee_amount),
+        }
+    }
+}
+
+enum Value {
+    Empty,
+    Text(String),
+    /** Formatted with two decimals */
+    Amount(f32),
+    Number(f32),
+}
+
+impl Value {
+    fn text(s: &str) -> Self {
+        Self::Text(s.to_string())
+    }
+}
+
+/**
+ * Streams CSV rows to a writer, nothing is kept in memory between two rows.
+ */
+#[derive(Debug)]
+pub struct Writer<W: std::io::Write> {
+    inner: W,
+    options: Options,
+    header_written: bool,
+}
+
+impl<W: std::io::Write> Writer<W> {
+    pub fn new(inner: W, options: Options) -> Self {
+        Self {
+            inner,
+            options,
+            header_written: false,
+        }
+    }
+
+    /**
+     * One row per transaction, product columns are left empty.
+     */
+    pub fn transaction(
+        &mut self,
+        transaction: &crate::Transaction,
+        columns: &[TransactionColumn],
+    ) -> crate::Result {
+        self.header(columns.iter().map(|x| x.header()))?;
+        self.row(columns.iter().map(|x| x.value(transaction, None)))
+    }
+
+    /**
+     * One row per product, transactions without product still get one row.
+     */
+    pub fn transaction_products(
+        &mut self,
+        transaction: &crate::Transaction,
+        columns: &[TransactionColumn],
+    ) -> crate::Result {
+        if transaction.products.is_empty() {
+            return self.transaction(transaction, columns);
+        }
+
+        self.header(columns.iter().map(|x| x.header()))?;
+
+        for product in &transaction.products {
+            self.row(columns.iter().map(|x| x.value(transaction, Some(product))))?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Exports the whole history matching the filter, one page at a time.
+     */
+    pub fn history(
+        &mut self,
+        transactions: &crate::services::Transactions,
+        filter: &crate::services::transactions::Filter,
+        columns: &[TransactionColumn],
+        per_product: bool,
+    ) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for page in transactions.pages(filter) {
+            for transaction in &page? {
+                if per_product {
+                    self.transaction_products(transaction, columns)?;
+                } else {
+                    self.transaction(transaction, columns)?;
+                }
+                count += 1;
+            }
+        }
+
+        self.flush()?;
+
+        Ok(count)
+    }
+
+    pub fn payout(&mut self, payout: &crate::Payout, columns: &[PayoutColumn]) -> crate::Result {
+        self.header(columns.iter().map(|x| x.header()))?;
+        self.row(columns.iter().map(|x| x.value(payout)))
+    }
+
+    pub fn event(&mut self, event: &crate::Event, columns: &[EventColumn]) -> crate::Result {
+        self.header(columns.iter().map(|x| x.header()))?;
+        self.row(columns.iter().map(|x| x.value(event)))
+    }
+
+    pub fn flush(&mut self) -> crate::Result {
+        self.inner.flush()?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn header<'h>(&mut self, headers: impl Iterator<Item = &'h str>) -> crate::Result {
+        if self.header_written || !self.options.header {
+            return Ok(());
+        }
+
+        self.header_written = true;
+        self.row(headers.map(Value::text))
+    }
+
+    fn row(&mut self, values: impl Iterator<Item = Value>) -> crate::Result {
+        let delimiter = self.options.delimiter.to_string();
+        let cells = values.map(|x| self.cell(x)).collect::<Vec<_>>();
+
+        writeln!(self.inner, "{}", cells.join(&delimiter))?;
+
+        Ok(())
+    }
+
+    fn cell(&self, value: Value) -> String {
+        let s = match value {
+            Value::Empty => return String::new(),
+            Value::Text(s) => s,
+            Value::Amount(n) => self.number(&format!("{n:.2}")),
+            Value::Number(n) => self.number(&n.to_string()),
+        };
+
+        if s.contains([self.options.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s
+        }
+    }
+
+    fn number(&self, s: &str) -> String {
+        s.replace('.', &self.options.decimal_separator.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn transaction() -> crate::Transaction {
+        serde_json::from_str(include_str!("../../fixtures/transaction.json")).unwrap()
+    }
+
+    #[test]
+    fn transaction_csv() -> crate::Result {
+        use crate::export::csv::TransactionColumn::*;
+
+        let mut writer = crate::export::csv::Writer::new(
+            Vec::new(),
+            crate::export::csv::Options::locale("de-DE"),
+        );
+
+        let columns = [TransactionCode, Amount, ProductName, ProductVatRate];
+        writer.transaction_products(&transaction(), &columns)?;
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "transaction_code;amount;product_name;product_vat_rate
+TEENSK4W2K;9,76;Coffee;0,19
+TEENSK4W2K;9,76;Croissant;0,07
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn payout_csv() -> crate::Result {
+        use crate::export::csv::PayoutColumn::*;
+
+        let mut writer =
+            crate::export::csv::Writer::new(Vec::new(), crate::export::csv::Options::default());
+
+        let payout = crate::Payout {
+            amount: 1234.5,
+            reference: "Payout, January".to_string(),
+
+            ..Default::default()
+        };
+        writer.payout(&payout, &[Amount, Reference])?;
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "amount,reference\n1234.50,\"Payout, January\"\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn event_csv() -> crate::Result {
+        let mut writer = crate::export::csv::Writer::new(
+            Vec::new(),
+            crate::export::csv::Options {
+                header: false,
+
+                ..Default::default()
+            },
+        );
+
+        for event in &transaction().events {
+            writer.event(event, &crate::export::csv::EventColumn::all())?;
+        }
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "1120432,4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c,PAYOUT,PAID_OUT,2021-01-07T06:00:00.000Z,9.76,0.19,1,0.00,0.00\n"
+        );
+
+        Ok(())
+    }
+}
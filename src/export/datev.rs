@@ -0,0 +1,553 @@
+/** Columns of the booking lines, the trailing optional columns are left out */
+const COLUMNS: &[&str] = &[
+    "Umsatz (ohne Soll/Haben-Kz)",
+    "Soll/Haben-Kennzeichen",
+    "WKZ Umsatz",
+    "Kurs",
+    "Basis-Umsatz",
+    "WKZ Basis-Umsatz",
+    "Konto",
+    "Gegenkonto (ohne BU-Schlüssel)",
+    "BU-Schlüssel",
+    "Belegdatum",
+    "Belegfeld 1",
+    "Belegfeld 2",
+    "Skonto",
+    "Buchungstext",
+];
+
+/**
+ * Revenue account of a VAT rate.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revenue {
+    /** `0.19` for 19 % */
+    pub vat_rate: f32,
+    pub account: String,
+    /** BU-Schlüssel, empty for automatic accounts */
+    pub tax_key: String,
+}
+
+/**
+ * Account mapping of the bookings.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accounts {
+    /** Chart of accounts written in the header, `03` or `04` */
+    pub chart: String,
+    /** SumUp balance, money in transit to the bank account */
+    pub transit: String,
+    pub bank: String,
+    /** Payment fees */
+    pub fees: String,
+    pub revenues: Vec<Revenue>,
+    /** Revenue without matching VAT rate, tips for example */
+    pub other_revenue: String,
+}
+
+impl Accounts {
+    /**
+     * SKR03 automatic accounts.
+     */
+    #[must_use]
+    pub fn skr03() -> Self {
+        Self {
+            chart: "03".to_string(),
+            transit: "1360".to_string(),
+            bank: "1200".to_string(),
+            fees: "4970".to_string(),
+            revenues: vec![revenue(0.19, "8400"), revenue(0.07, "8300")],
+            other_revenue: "8200".to_string(),
+        }
+    }
+
+    /**
+     * SKR04 automatic accounts.
+     */
+    #[must_use]
+    pub fn skr04() -> Self {
+        Self {
+            chart: "04".to_string(),
+            transit: "1460".to_string(),
+            bank: "1800".to_string(),
+            fees: "6855".to_string(),
+            revenues: vec![revenue(0.19, "4400"), revenue(0.07, "4300")],
+            other_revenue: "4200".to_string(),
+        }
+    }
+
+    fn revenue(&self, vat_rate: f32) -> (&str, &str) {
+        self.revenues
+            .iter()
+            .find(|x| (x.vat_rate - vat_rate).abs() < 0.001)
+            .map_or((self.other_revenue.as_str(), ""), |x| {
+                (x.account.as_str(), x.tax_key.as_str())
+            })
+    }
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self::skr03()
+    }
+}
+
+fn revenue(vat_rate: f32, account: &str) -> Revenue {
+    Revenue {
+        vat_rate,
+        account: account.to_string(),
+        tax_key: String::new(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /** Beraternummer of the tax advisor */
+    pub consultant_number: u32,
+    /** Mandantennummer of the merchant */
+    pub client_number: u32,
+    /** First day of the fiscal year, `YYYY-MM-DD` */
+    pub fiscal_year_start: String,
+    /** First day of the export, `YYYY-MM-DD` */
+    pub from_date: String,
+    /** Last day of the export, `YYYY-MM-DD` */
+    pub to_date: String,
+    /** Bezeichnung of the batch */
+    pub description: String,
+    /** ISO 8601 generation time, now if empty */
+    pub created_at: String,
+    pub accounts: Accounts,
+}
+
+/**
+ * Streams a DATEV EXTF Buchungsstapel (format 700, version 13), Windows-1252 encoded.
+ *
+ * Sales are booked gross per VAT rate of their products on the revenue accounts, the remainder
+ * not covered by the products (tips) on `other_revenue`. Refunds and chargebacks are reversed on
+ * the same accounts, in proportion of the gross amount of each VAT rate. Payouts are booked from
+ * the transit account to the bank account, their fees on the fees account.
+ */
+#[derive(Debug)]
+pub struct Writer<W: std::io::Write> {
+    inner: W,
+    options: Options,
+    header_written: bool,
+}
+
+impl<W: std::io::Write> Writer<W> {
+    pub fn new(inner: W, options: Options) -> Self {
+        Self {
+            inner,
+            options,
+            header_written: false,
+        }
+    }
+
+    pub fn transaction(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        let parts = parts(transaction);
+        let accounts = self.options.accounts.clone();
+
+        for entry in super::Entry::transaction(transaction) {
+            let document = document(transaction, &entry);
+
+            // Refunds and chargebacks are spread over the parts in proportion of the sale
+            let ratio = match entry.kind {
+                super::Kind::Sale => 1.,
+                _ if transaction.amount == 0. => 0.,
+                _ => entry.amount / transaction.amount,
+            };
+            let mut remaining = entry.amount;
+
+            for (index, (vat_rate, gross)) in parts.iter().enumerate() {
+                let amount = if index + 1 == parts.len() {
+                    remaining
+                } else {
+                    (gross * ratio * 100.).round() / 100.
+                };
+                remaining -= amount;
+
+                if amount.abs() < 0.005 {
+                    continue;
+                }
+
+                let (account, tax_key, text) = match vat_rate {
+                    Some(vat_rate) => {
+                        let (account, tax_key) = accounts.revenue(*vat_rate);
+
+                        (
+                            account,
+                            tax_key,
+                            format!("{} {}%", entry.name, (vat_rate * 100.).round()),
+                        )
+                    }
+                    None => (accounts.other_revenue.as_str(), "", entry.name.clone()),
+                };
+
+                self.booking(&Booking {
+                    amount,
+                    account: &accounts.transit,
+                    contra_account: account,
+                    tax_key,
+                    entry: &entry,
+                    document: &document,
+                    text: &text,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn payout(&mut self, payout: &crate::Payout) -> crate::Result {
+        let accounts = self.options.accounts.clone();
+
+        for entry in super::Entry::payout(payout) {
+            let account = match entry.kind {
+                super::Kind::Fee => &accounts.fees,
+                _ => &accounts.bank,
+            };
+
+            self.booking(&Booking {
+                amount: -entry.amount,
+                account,
+                contra_account: &accounts.transit,
+                tax_key: "",
+                entry: &entry,
+                document: &entry.id,
+                text: &entry.name,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Exports the transactions matching the filter, one page at a time.
+     */
+    pub fn history(
+        &mut self,
+        transactions: &crate::services::Transactions,
+        filter: &crate::services::transactions::Filter,
+    ) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for page in transactions.pages(filter) {
+            for transaction in &page? {
+                self.transaction(transaction)?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /**
+     * Exports the payouts matching the filter.
+     */
+    pub fn payouts(
+        &mut self,
+        payouts: &crate::services::Payouts,
+        filter: &crate::services::payouts::Filter,
+    ) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for page in payouts.pages(filter) {
+            for payout in &page? {
+                self.payout(payout)?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    pub fn flush(&mut self) -> crate::Result {
+        self.header()?;
+        self.inner.flush()?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn booking(&mut self, booking: &Booking) -> crate::Result {
+        self.header()?;
+
+        let date = crate::date::Date::parse(&booking.entry.date)
+            .map(|x| format!("{:02}{:02}", x.day, x.month))
+            .unwrap_or_default();
+
+        let fields = [
+            amount(booking.amount.abs()),
+            text(if booking.amount < 0. { "H" } else { "S" }, 1),
+            text(&booking.entry.currency, 3),
+            String::new(),
+            String::new(),
+            String::new(),
+            booking.account.to_string(),
+            booking.contra_account.to_string(),
+            text(booking.tax_key, 4),
+            date,
+            text(booking.document, 36),
+            String::new(),
+            String::new(),
+            text(booking.text, 60),
+        ];
+
+        self.line(&fields.join(";"))
+    }
+
+    fn header(&mut self) -> crate::Result {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+
+        let options = &self.options;
+        let created_at = if options.created_at.is_empty() {
+            crate::date::now_rfc3339()
+        } else {
+            options.created_at.clone()
+        };
+        let created_at = format!("{:0<17}", digits(&created_at, 17));
+
+        let header = [
+            text("EXTF", 4),
+            "700".to_string(),
+            "21".to_string(),
+            text("Buchungsstapel", 14),
+            "13".to_string(),
+            created_at,
+            String::new(),
+            text("SU", 2),
+            text("", 25),
+            text("", 25),
+            options.consultant_number.to_string(),
+            options.client_number.to_string(),
+            digits(&options.fiscal_year_start, 8),
+            options.accounts.transit.len().to_string(),
+            digits(&options.from_date, 8),
+            digits(&options.to_date, 8),
+            text(&options.description, 30),
+            text("", 2),
+            "1".to_string(),
+            "0".to_string(),
+            "0".to_string(),
+            text("EUR", 3),
+            String::new(),
+            text("", 2),
+            String::new(),
+            String::new(),
+            text(&options.accounts.chart, 2),
+            String::new(),
+            String::new(),
+            text("", 16),
+            text("", 16),
+        ]
+        .join(";");
+        self.line(&header)?;
+
+        let columns = COLUMNS.iter().map(|x| text(x, 64)).collect::<Vec<_>>();
+        self.line(&columns.join(";"))
+    }
+
+    fn line(&mut self, line: &str) -> crate::Result {
+        self.inner.write_all(&windows_1252(line))?;
+        self.inner.write_all(b"\r\n")?;
+
+        Ok(())
+    }
+}
+
+struct Booking<'b> {
+    /** Positive for a debit of `account`, negative for a credit */
+    amount: f32,
+    account: &'b str,
+    contra_account: &'b str,
+    tax_key: &'b str,
+    entry: &'b super::Entry,
+    /** Belegfeld 1 */
+    document: &'b str,
+    text: &'b str,
+}
+
+/**
+ * Gross amount per VAT rate of the transaction products, falls back to the transaction VAT
+ * amount without product. The remainder not covered by the products, tips for example, comes
+ * last without rate.
+ */
+fn parts(transaction: &crate::Transaction) -> Vec<(Option<f32>, f32)> {
+    let mut parts = Vec::<(Option<f32>, f32)>::new();
+
+    for product in &transaction.products {
+        match parts
+            .iter_mut()
+            .find(|x| x.0.is_some_and(|x| (x - product.vat_rate).abs() < 0.001))
+        {
+            Some(part) => part.1 += product.total_with_vat,
+            None => parts.push((Some(product.vat_rate), product.total_with_vat)),
+        }
+    }
+
+    let net = transaction.amount - transaction.tip_amount - transaction.vat_amount;
+    // Without net amount there is no rate to tell, all goes to the remainder
+    if parts.is_empty() && transaction.vat_amount != 0. && net.abs() >= 0.005 {
+        let rate = (transaction.vat_amount / net * 100.).round() / 100.;
+
+        parts.push((Some(rate), net + transaction.vat_amount));
+    }
+
+    let remainder = transaction.amount - parts.iter().map(|x| x.1).sum::<f32>();
+    if remainder.abs() >= 0.005 || parts.is_empty() {
+        parts.push((None, remainder));
+    }
+
+    parts
+}
+
+/**
+ * Belegfeld 1 is limited to 36 characters: the ids of the refunds and chargebacks start with
+ * the transaction code instead of the transaction id, which is already 36 characters long.
+ */
+fn document(transaction: &crate::Transaction, entry: &super::Entry) -> String {
+    if entry.id.chars().count() <= 36 || transaction.transaction_code.is_empty() {
+        return entry.id.clone();
+    }
+
+    match entry.id.strip_prefix(&transaction.id) {
+        Some(suffix) if !transaction.id.is_empty() => {
+            format!("{}{suffix}", transaction.transaction_code)
+        }
+        _ => entry.id.clone(),
+    }
+}
+
+fn amount(n: f32) -> String {
+    format!("{n:.2}").replace('.', ",")
+}
+
+fn text(s: &str, max: usize) -> String {
+    let s = s.chars().take(max).collect::<String>();
+
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+fn digits(s: &str, max: usize) -> String {
+    s.chars().filter(char::is_ascii_digit).take(max).collect()
+}
+
+/**
+ * Latin-1 characters are kept as is, `€` is mapped and anything else becomes `?`.
+ */
+fn windows_1252(s: &str) -> Vec<u8> {
+    s.chars()
+        .map(|c| match c {
+            '€' => 0x80,
+            c if (c as u32) < 0x80 || (0xA0..=0xFF).contains(&(c as u32)) => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    fn options() -> crate::export::datev::Options {
+        crate::export::datev::Options {
+            consultant_number: 1_001,
+            client_number: 42,
+            fiscal_year_start: "2021-01-01".to_string(),
+            from_date: "2021-01-01".to_string(),
+            to_date: "2021-01-31".to_string(),
+            description: "SumUp Januar".to_string(),
+            created_at: "2021-02-01T08:00:00Z".to_string(),
+            accounts: crate::export::datev::Accounts::skr03(),
+        }
+    }
+
+    #[test]
+    fn write() -> crate::Result {
+        let payout = crate::Payout {
+            amount: 9.76,
+            currency: "EUR".to_string(),
+            date: "2021-01-07".to_string(),
+            fee: 0.19,
+            id: 42,
+            reference: "PO-0107".to_string(),
+
+            ..Default::default()
+        };
+
+        let mut writer = crate::export::datev::Writer::new(Vec::new(), options());
+        writer.transaction(&crate::export::test::transaction())?;
+        writer.payout(&payout)?;
+
+        assert_eq!(
+            writer.into_inner(),
+            include_bytes!("../../fixtures/export/datev.csv")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn skr04() -> crate::Result {
+        let mut transaction = crate::export::test::transaction();
+        transaction.products.clear();
+        transaction.events.clear();
+        transaction.amount = 12.9;
+        transaction.vat_amount = 1.9;
+        transaction.tip_amount = 1.;
+
+        let options = crate::export::datev::Options {
+            accounts: crate::export::datev::Accounts::skr04(),
+
+            ..options()
+        };
+
+        let mut writer = crate::export::datev::Writer::new(Vec::new(), options);
+        writer.transaction(&transaction)?;
+
+        let csv = writer.into_inner();
+        let csv = String::from_utf8_lossy(&csv);
+        let lines = csv.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("11,90;\"S\";\"EUR\";;;;1460;4400;\"\";0501;"));
+        assert!(lines[1].starts_with("1,00;\"S\";\"EUR\";;;;1460;4200;"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_net() -> crate::Result {
+        let mut transaction = crate::export::test::transaction();
+        transaction.products.clear();
+        transaction.events.clear();
+        transaction.amount = 1.9;
+        transaction.vat_amount = 1.9;
+        transaction.tip_amount = 0.;
+
+        let mut writer = crate::export::datev::Writer::new(Vec::new(), options());
+        writer.transaction(&transaction)?;
+
+        let csv = writer.into_inner();
+        let csv = String::from_utf8_lossy(&csv);
+        let lines = csv.lines().skip(2).collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("1,90;\"S\";\"EUR\";;;;1360;"));
+        assert!(!csv.contains("inf") && !csv.contains("NaN"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn windows_1252() {
+        assert_eq!(
+            super::windows_1252("Mandant Müller 5 €"),
+            b"Mandant M\xFCller 5 \x80"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+pub mod csv;
+pub mod datev;
+pub mod ofx;
+pub mod qif;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    Sale,
+    Refund,
+    ChargeBack,
+    /** Money transferred from SumUp to the bank account */
+    Payout,
+    Fee,
+}
+
+/**
+ * One booking of the OFX and QIF exports.
+ *
+ * The `id` is derived from the transaction id or the payout id, so exporting the same period
+ * twice gives the same ids and importing it again doesn't duplicate entries.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub id: String,
+    pub kind: Kind,
+    /** `YYYY-MM-DD` or ISO 8601 timestamp */
+    pub date: String,
+    /** Positive for money received, negative for money leaving the SumUp balance */
+    pub amount: f32,
+    pub currency: String,
+    pub name: String,
+    pub memo: String,
+}
+
+impl Entry {
+    /**
+     * The sale, if money was collected, followed by its refunds and chargebacks which didn't fail.
+     */
+    #[must_use]
+    pub fn transaction(transaction: &crate::Transaction) -> Vec<Self> {
+        let id = if transaction.id.is_empty() {
+            &transaction.transaction_code
+        } else {
+            &transaction.id
+        };
+
+        let mut entries = Vec::new();
+
+        if !matches!(
+            transaction.status,
+            crate::Status::Cancelled | crate::Status::Failed
+        ) {
+            entries.push(Self {
+                id: id.clone(),
+                kind: Kind::Sale,
+                date: transaction.timestamp.clone(),
+                amount: transaction.amount,
+                currency: transaction.currency.clone(),
+                name: format!("SumUp {}", transaction.transaction_code),
+                memo: transaction.product_summary.clone(),
+            });
+        }
+
+        let events = transaction
+            .events
+            .iter()
+            .filter(|x| x.transaction_id == transaction.id && x.status != "FAILED");
+
+        for event in events {
+            let (kind, label) = match event.ty.as_str() {
+                "REFUND" => (Kind::Refund, "Refund"),
+                "CHARGE_BACK" => (Kind::ChargeBack, "Chargeback"),
+                _ => continue,
+            };
+
+            entries.push(Self {
+                id: format!("{id}-{}-{}", event.ty, event.id),
+                kind,
+                date: event.timestamp.clone(),
+                amount: -event.amount.abs(),
+                currency: transaction.currency.clone(),
+                name: format!("SumUp {label} {}", transaction.transaction_code),
+                memo: transaction.product_summary.clone(),
+            });
+        }
+
+        entries
+    }
+
+    /**
+     * The payout line, fee deducted, followed by its fee.
+     */
+    #[must_use]
+    pub fn payout(payout: &crate::Payout) -> Vec<Self> {
+        let id = format!("PAYOUT-{}", payout.id);
+        let memo = if payout.transaction_code.is_empty() {
+            payout.reference.clone()
+        } else {
+            format!("{} {}", payout.reference, payout.transaction_code)
+        };
+
+        let mut entries = vec![Self {
+            id: id.clone(),
+            kind: Kind::Payout,
+            date: payout.date.clone(),
+            amount: -(payout.amount - payout.fee),
+            currency: payout.currency.clone(),
+            name: format!("SumUp payout {}", payout.reference),
+            memo: memo.clone(),
+        }];
+
+        if payout.fee != 0. {
+            entries.push(Self {
+                id: format!("{id}-FEE"),
+                kind: Kind::Fee,
+                date: payout.date.clone(),
+                amount: -payout.fee,
+                currency: payout.currency.clone(),
+                name: "SumUp fee".to_string(),
+                memo,
+            });
+        }
+
+        entries
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    pub(crate) fn transaction() -> crate::Transaction {
+        let mut transaction: crate::Transaction =
+            serde_json::from_str(include_str!("../../fixtures/transaction.json")).unwrap();
+
+        let mut refund = transaction.events[0].clone();
+        refund.id = 1_120_500;
+        refund.ty = "REFUND".to_string();
+        refund.amount = 3.96;
+        refund.fee_amount = 0.;
+        refund.timestamp = "2021-01-06T09:00:00.000Z".to_string();
+        transaction.events.push(refund);
+
+        transaction
+    }
+
+    #[test]
+    fn transaction_entries() {
+        let entries = crate::export::Entry::transaction(&transaction());
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, "4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c");
+        assert_eq!(entries[0].amount, 9.76);
+        assert_eq!(
+            entries[1].id,
+            "4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c-REFUND-1120500"
+        );
+        assert_eq!(entries[1].amount, -3.96);
+        assert_eq!(entries, crate::export::Entry::transaction(&transaction()));
+
+        let mut failed = transaction();
+        failed.events.last_mut().unwrap().status = "FAILED".to_string();
+        assert_eq!(crate::export::Entry::transaction(&failed).len(), 1);
+    }
+
+    #[test]
+    fn payout_entries() {
+        let payout = crate::Payout {
+            amount: 9.76,
+            fee: 0.19,
+            id: 42,
+            reference: "PO-0107".to_string(),
+
+            ..Default::default()
+        };
+
+        let entries = crate::export::Entry::payout(&payout);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, "PAYOUT-42");
+        assert!((entries[0].amount + 9.57).abs() < 0.001);
+        assert_eq!(entries[1].id, "PAYOUT-42-FEE");
+        assert_eq!(entries[1].amount, -0.19);
+    }
+}
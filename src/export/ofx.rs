@@ -0,0 +1,219 @@
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /** Account identifier and currency, `owner` isn't exported */
+    pub account: crate::statement::Account,
+    /** Bank routing number, most tools accept any value */
+    pub bank_id: String,
+    /** First day of the export, `YYYY-MM-DD` */
+    pub from_date: String,
+    /** Last day of the export, `YYYY-MM-DD` */
+    pub to_date: String,
+    /** ISO 8601 generation time, now if empty */
+    pub created_at: String,
+    /** Ledger balance at the end of the period, the sum of the exported entries if `None` */
+    pub balance: Option<f32>,
+}
+
+/**
+ * Streams an OFX 2.2 bank statement, call [`Writer::finish`] to close the document.
+ */
+#[derive(Debug)]
+pub struct Writer<W: std::io::Write> {
+    inner: W,
+    options: Options,
+    header_written: bool,
+    total: f32,
+}
+
+impl<W: std::io::Write> Writer<W> {
+    pub fn new(inner: W, options: Options) -> Self {
+        Self {
+            inner,
+            options,
+            header_written: false,
+            total: 0.,
+        }
+    }
+
+    pub fn entry(&mut self, entry: &super::Entry) -> crate::Result {
+        self.header()?;
+
+        let ty = match entry.kind {
+            super::Kind::Sale => "CREDIT",
+            super::Kind::Refund | super::Kind::ChargeBack => "DEBIT",
+            super::Kind::Payout => "XFER",
+            super::Kind::Fee => "FEE",
+        };
+
+        let w = &mut self.inner;
+        writeln!(w, "<STMTTRN>")?;
+        writeln!(w, "<TRNTYPE>{ty}</TRNTYPE>")?;
+        writeln!(w, "<DTPOSTED>{}</DTPOSTED>", date(&entry.date))?;
+        writeln!(w, "<TRNAMT>{:.2}</TRNAMT>", entry.amount)?;
+        writeln!(w, "<FITID>{}</FITID>", escape(&entry.id))?;
+        writeln!(w, "<NAME>{}</NAME>", escape(&truncate(&entry.name, 32)))?;
+        if !entry.memo.is_empty() {
+            writeln!(w, "<MEMO>{}</MEMO>", escape(&truncate(&entry.memo, 255)))?;
+        }
+        writeln!(w, "</STMTTRN>")?;
+
+        self.total += entry.amount;
+
+        Ok(())
+    }
+
+    pub fn transaction(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        for entry in super::Entry::transaction(transaction) {
+            self.entry(&entry)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn payout(&mut self, payout: &crate::Payout) -> crate::Result {
+        for entry in super::Entry::payout(payout) {
+            self.entry(&entry)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Writes the ledger balance and closes the document.
+     */
+    pub fn finish(mut self) -> crate::Result<W> {
+        self.header()?;
+
+        let balance = self.options.balance.unwrap_or(self.total);
+        let w = &mut self.inner;
+
+        writeln!(w, "</BANKTRANLIST>")?;
+        writeln!(w, "<LEDGERBAL>")?;
+        writeln!(w, "<BALAMT>{balance:.2}</BALAMT>")?;
+        writeln!(w, "<DTASOF>{}</DTASOF>", date(&self.options.to_date))?;
+        writeln!(w, "</LEDGERBAL>")?;
+        writeln!(w, "</STMTRS>")?;
+        writeln!(w, "</STMTTRNRS>")?;
+        writeln!(w, "</BANKMSGSRSV1>")?;
+        writeln!(w, "</OFX>")?;
+        w.flush()?;
+
+        Ok(self.inner)
+    }
+
+    fn header(&mut self) -> crate::Result {
+        if self.header_written {
+            return Ok(());
+        }
+        self.header_written = true;
+
+        let created_at = if self.options.created_at.is_empty() {
+            crate::date::now_rfc3339()
+        } else {
+            self.options.created_at.clone()
+        };
+        let account = &self.options.account;
+        let w = &mut self.inner;
+
+        writeln!(
+            w,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+        )?;
+        writeln!(
+            w,
+            r#"<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>"#
+        )?;
+        writeln!(w, "<OFX>")?;
+        writeln!(w, "<SIGNONMSGSRSV1>")?;
+        writeln!(w, "<SONRS>")?;
+        status(w)?;
+        writeln!(w, "<DTSERVER>{}</DTSERVER>", date(&created_at))?;
+        writeln!(w, "<LANGUAGE>ENG</LANGUAGE>")?;
+        writeln!(w, "</SONRS>")?;
+        writeln!(w, "</SIGNONMSGSRSV1>")?;
+        writeln!(w, "<BANKMSGSRSV1>")?;
+        writeln!(w, "<STMTTRNRS>")?;
+        writeln!(w, "<TRNUID>0</TRNUID>")?;
+        status(w)?;
+        writeln!(w, "<STMTRS>")?;
+        writeln!(w, "<CURDEF>{}</CURDEF>", escape(&account.currency))?;
+        writeln!(w, "<BANKACCTFROM>")?;
+        writeln!(w, "<BANKID>{}</BANKID>", escape(&self.options.bank_id))?;
+        writeln!(w, "<ACCTID>{}</ACCTID>", escape(&account.id))?;
+        writeln!(w, "<ACCTTYPE>CHECKING</ACCTTYPE>")?;
+        writeln!(w, "</BANKACCTFROM>")?;
+        writeln!(w, "<BANKTRANLIST>")?;
+        writeln!(w, "<DTSTART>{}</DTSTART>", date(&self.options.from_date))?;
+        writeln!(w, "<DTEND>{}</DTEND>", date(&self.options.to_date))?;
+
+        Ok(())
+    }
+}
+
+fn status(w: &mut impl std::io::Write) -> crate::Result {
+    writeln!(w, "<STATUS>")?;
+    writeln!(w, "<CODE>0</CODE>")?;
+    writeln!(w, "<SEVERITY>INFO</SEVERITY>")?;
+    writeln!(w, "</STATUS>")?;
+
+    Ok(())
+}
+
+/**
+ * `2021-01-05T10:29:31.000Z` → `20210105102931`, `2021-01-07` → `20210107`
+ */
+fn date(s: &str) -> String {
+    s.chars().filter(char::is_ascii_digit).take(14).collect()
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    s.chars().take(max).collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn write() -> crate::Result {
+        let options = crate::export::ofx::Options {
+            account: crate::statement::Account {
+                id: "MH4H92C7".to_string(),
+                currency: "EUR".to_string(),
+                owner: None,
+            },
+            bank_id: "SUMUP".to_string(),
+            from_date: "2021-01-01".to_string(),
+            to_date: "2021-01-31".to_string(),
+            created_at: "2021-02-01T08:00:00Z".to_string(),
+            balance: None,
+        };
+
+        let payout = crate::Payout {
+            amount: 9.76,
+            currency: "EUR".to_string(),
+            date: "2021-01-07".to_string(),
+            fee: 0.19,
+            id: 42,
+            reference: "PO-0107".to_string(),
+            transaction_code: "TEENSK4W2K".to_string(),
+
+            ..Default::default()
+        };
+
+        let mut writer = crate::export::ofx::Writer::new(Vec::new(), options);
+        writer.transaction(&crate::export::test::transaction())?;
+        writer.payout(&payout)?;
+
+        assert_eq!(
+            String::from_utf8(writer.finish()?).unwrap(),
+            include_str!("../../fixtures/export/transactions.ofx")
+        );
+
+        Ok(())
+    }
+}
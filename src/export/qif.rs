@@ -0,0 +1,107 @@
+/**
+ * Streams a QIF bank account, the entry id is written as check number (`N`) since QIF has no
+ * dedicated identifier field.
+ */
+#[derive(Debug)]
+pub struct Writer<W: std::io::Write> {
+    inner: W,
+    header_written: bool,
+}
+
+impl<W: std::io::Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            header_written: false,
+        }
+    }
+
+    pub fn entry(&mut self, entry: &super::Entry) -> crate::Result {
+        if !self.header_written {
+            self.header_written = true;
+            writeln!(self.inner, "!Type:Bank")?;
+        }
+
+        let w = &mut self.inner;
+        writeln!(w, "D{}", date(&entry.date))?;
+        writeln!(w, "T{:.2}", entry.amount)?;
+        writeln!(w, "N{}", line(&entry.id))?;
+        writeln!(w, "P{}", line(&entry.name))?;
+        if !entry.memo.is_empty() {
+            writeln!(w, "M{}", line(&entry.memo))?;
+        }
+        writeln!(w, "^")?;
+
+        Ok(())
+    }
+
+    pub fn transaction(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        for entry in super::Entry::transaction(transaction) {
+            self.entry(&entry)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn payout(&mut self, payout: &crate::Payout) -> crate::Result {
+        for entry in super::Entry::payout(payout) {
+            self.entry(&entry)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> crate::Result {
+        self.inner.flush()?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/**
+ * `MM/DD/YYYY`, the most widely accepted QIF date format.
+ */
+fn date(s: &str) -> String {
+    match crate::date::Date::parse(s) {
+        Some(date) => format!("{:02}/{:02}/{:04}", date.month, date.day, date.year),
+        None => s.to_string(),
+    }
+}
+
+/** QIF fields are line based */
+fn line(s: &str) -> String {
+    s.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn write() -> crate::Result {
+        let mut writer = crate::export::qif::Writer::new(Vec::new());
+        writer.transaction(&crate::export::test::transaction())?;
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "!Type:Bank
+D01/05/2021
+T9.76
+N4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c
+PSumUp TEENSK4W2K
+MCoffee, Croissant
+^
+D01/06/2021
+T-3.96
+N4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c-REFUND-1120500
+PSumUp Refund TEENSK4W2K
+MCoffee, Croissant
+^
+"
+        );
+
+        Ok(())
+    }
+}
@@ -1,10 +1,26 @@
 #[warn(warnings)]
+pub mod card;
+pub mod cassette;
 pub mod config;
 pub mod errors;
+pub mod escpos;
+pub mod export;
+pub mod middleware;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod reconciliation;
 pub mod services;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod statement;
+pub mod sync;
+pub mod vat;
+pub mod webhooks;
 
 mod api;
+mod date;
 mod entity;
+mod redact;
 
 pub use config::Config;
 pub use entity::*;
@@ -27,7 +43,9 @@ impl SumUp {
     }
 
     pub fn from(config: Config) -> Result<Self> {
-        let api = Api::new();
+        let api = Api::new()
+            .base_url(config.base_url.clone())
+            .middlewares(config.middlewares.clone());
         let authorization = services::Authorization::new(&api, &config);
         let access_token = authorization.token()?;
 
@@ -61,7 +79,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/account-details/>
      */
     #[must_use]
-    pub fn account(&self) -> crate::services::Account {
+    pub fn account(&self) -> crate::services::Account<'_> {
         services::Account::new(&self.api, &self.access_token)
     }
 
@@ -69,7 +87,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/authorization/>
      */
     #[must_use]
-    pub fn authorization(&self) -> crate::services::Authorization {
+    pub fn authorization(&self) -> crate::services::Authorization<'_> {
         services::Authorization::new(&self.api, &self.config)
     }
 
@@ -77,7 +95,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/checkouts/>
      */
     #[must_use]
-    pub fn checkout(&self) -> crate::services::Checkout {
+    pub fn checkout(&self) -> crate::services::Checkout<'_> {
         services::Checkout::new(&self.api, &self.access_token)
     }
 
@@ -85,7 +103,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/customers/>
      */
     #[must_use]
-    pub fn customer(&self) -> crate::services::Customer {
+    pub fn customer(&self) -> crate::services::Customer<'_> {
         services::Customer::new(&self.api, &self.access_token)
     }
 
@@ -93,12 +111,12 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/merchant-account/>
      */
     #[must_use]
-    pub fn merchant(&self) -> crate::services::Merchant {
+    pub fn merchant(&self) -> crate::services::Merchant<'_> {
         services::Merchant::new(&self.api, &self.access_token)
     }
 
     #[must_use]
-    pub fn payouts(&self) -> crate::services::Payouts {
+    pub fn payouts(&self) -> crate::services::Payouts<'_> {
         services::Payouts::new(&self.api, &self.access_token)
     }
 
@@ -106,7 +124,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/personal-account/>
      */
     #[must_use]
-    pub fn personal(&self) -> crate::services::Personal {
+    pub fn personal(&self) -> crate::services::Personal<'_> {
         services::Personal::new(&self.api, &self.access_token)
     }
 
@@ -114,7 +132,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/subaccounts/>
      */
     #[must_use]
-    pub fn subaccounts(&self) -> crate::services::Subaccounts {
+    pub fn subaccounts(&self) -> crate::services::Subaccounts<'_> {
         services::Subaccounts::new(&self.api, &self.access_token)
     }
 
@@ -122,7 +140,7 @@ impl SumUp {
      * <https://developer.sumup.com/docs/api/transactions/>
      */
     #[must_use]
-    pub fn transactions(&self) -> crate::services::Transactions {
+    pub fn transactions(&self) -> crate::services::Transactions<'_> {
         services::Transactions::new(&self.api, &self.access_token)
     }
 }
@@ -131,14 +149,45 @@ impl SumUp {
 mod test {
     static INIT: std::sync::Once = std::sync::Once::new();
 
-    pub(crate) fn api() -> crate::Result<crate::SumUp> {
+    /**
+     * Client of the live API when `CLIENT_ID` is set, of a [`crate::mock::Server`] otherwise.
+     */
+    pub(crate) struct Api {
+        api: crate::SumUp,
+        _server: Option<crate::mock::Server>,
+    }
+
+    impl std::ops::Deref for Api {
+        type Target = crate::SumUp;
+
+        fn deref(&self) -> &Self::Target {
+            &self.api
+        }
+    }
+
+    impl std::ops::DerefMut for Api {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.api
+        }
+    }
+
+    pub(crate) fn api() -> crate::Result<Api> {
         INIT.call_once(|| {
             dotenvy::dotenv().ok();
             env_logger::init();
         });
 
+        let Ok(client_id) = std::env::var("CLIENT_ID") else {
+            let server = crate::mock::Server::start()?;
+
+            return Ok(Api {
+                api: crate::SumUp::from(server.config())?,
+                _server: Some(server),
+            });
+        };
+
         let config = crate::Config {
-            client_id: std::env::var("CLIENT_ID").unwrap(),
+            client_id,
             client_secret: std::env::var("CLIENT_SECRET").unwrap(),
             username: std::env::var("USERNAME").ok(),
             password: std::env::var("PASSWORD").ok(),
@@ -147,7 +196,10 @@ mod test {
             ..Default::default()
         };
 
-        crate::SumUp::from(config)
+        Ok(Api {
+            api: crate::SumUp::from(config)?,
+            _server: None,
+        })
     }
 
     #[test]
@@ -0,0 +1,355 @@
+/**
+ * Outgoing request, as seen and modified by the middlewares.
+ */
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: reqwest::Method,
+    /** Path template, like `/checkouts/{id}` */
+    pub endpoint: &'static str,
+    pub url: String,
+    pub headers: reqwest::header::HeaderMap,
+    /** Serialized JSON payload, sent as is */
+    pub body: Option<String>,
+}
+
+impl Request {
+    /** `POST`, `PUT`, `PATCH` and `DELETE` requests */
+    #[must_use]
+    pub fn is_mutating(&self) -> bool {
+        !matches!(
+            self.method,
+            reqwest::Method::GET | reqwest::Method::HEAD | reqwest::Method::OPTIONS
+        )
+    }
+
+    /**
+     * Parses the payload. Changing the returned value does not change the request, replace
+     * `body` for that.
+     */
+    pub fn json(&self) -> crate::Result<Option<serde_json::Value>> {
+        self.body
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(crate::Error::from)
+    }
+}
+
+/**
+ * Raw response, deserialized once every middleware returned.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Response {
+    pub status: u16,
+    pub headers: reqwest::header::HeaderMap,
+    pub body: String,
+}
+
+impl Response {
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/**
+ * Wraps the requests sent by [`crate::SumUp`].
+ *
+ * A middleware may modify the request before calling `next.run(request)`, inspect the response
+ * or the error it returns, or answer without calling `next` at all.
+ */
+pub trait Middleware: Send + Sync {
+    fn handle(&self, request: Request, next: Next<'_>) -> crate::Result<Response>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(Request, Next<'_>) -> crate::Result<Response> + Send + Sync,
+{
+    fn handle(&self, request: Request, next: Next<'_>) -> crate::Result<Response> {
+        self(request, next)
+    }
+}
+
+/**
+ * Rest of the chain, ending with the HTTP call.
+ */
+pub struct Next<'a> {
+    middlewares: &'a [std::sync::Arc<dyn Middleware>],
+    transport: fn(Request) -> crate::Result<Response>,
+}
+
+impl Next<'_> {
+    pub fn run(self, request: Request) -> crate::Result<Response> {
+        match self.middlewares.split_first() {
+            Some((middleware, middlewares)) => middleware.handle(
+                request,
+                Next {
+                    middlewares,
+                    transport: self.transport,
+                },
+            ),
+            None => (self.transport)(request),
+        }
+    }
+}
+
+/**
+ * Middlewares applied in order, the first one sees the request first and the response last.
+ */
+#[derive(Clone, Default)]
+pub struct Chain(Vec<std::sync::Arc<dyn Middleware>>);
+
+impl Chain {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.push(std::sync::Arc::new(middleware));
+        self
+    }
+
+    /**
+     * Appends a shared middleware, to keep reading a [`Timing`] for example.
+     */
+    pub fn push(&mut self, middleware: std::sync::Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn run(
+        &self,
+        request: Request,
+        transport: fn(Request) -> crate::Result<Response>,
+    ) -> crate::Result<Response> {
+        Next {
+            middlewares: &self.0,
+            transport,
+        }
+        .run(request)
+    }
+}
+
+impl std::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Chain({} middlewares)", self.0.len())
+    }
+}
+
+/**
+ * Logs one line per request: method, endpoint, status or error, and duration.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Logging {
+    pub level: log::Level,
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Self {
+            level: log::Level::Info,
+        }
+    }
+}
+
+impl Middleware for Logging {
+    fn handle(&self, request: Request, next: Next<'_>) -> crate::Result<Response> {
+        let method = request.method.clone();
+        let endpoint = request.endpoint;
+        let start = std::time::Instant::now();
+
+        let result = next.run(request);
+
+        match &result {
+            Ok(response) => log::log!(
+                self.level,
+                "{method} {endpoint} {} in {:?}",
+                response.status,
+                start.elapsed()
+            ),
+            Err(err) => log::log!(
+                self.level,
+                "{method} {endpoint} failed in {:?}: {err}",
+                start.elapsed()
+            ),
+        }
+
+        result
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    pub calls: u32,
+    /** Transport errors and non 2xx responses */
+    pub errors: u32,
+    pub total: std::time::Duration,
+    pub max: std::time::Duration,
+}
+
+impl Stats {
+    #[must_use]
+    pub fn mean(&self) -> std::time::Duration {
+        self.total.checked_div(self.calls).unwrap_or_default()
+    }
+}
+
+/**
+ * Measures the latency of the requests, per endpoint.
+ */
+#[derive(Debug, Default)]
+pub struct Timing {
+    stats: std::sync::Mutex<std::collections::BTreeMap<(String, &'static str), Stats>>,
+}
+
+impl Timing {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Stats by `(method, endpoint)`.
+     */
+    #[must_use]
+    pub fn stats(&self) -> std::collections::BTreeMap<(String, &'static str), Stats> {
+        self.lock().clone()
+    }
+
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(
+        &self,
+    ) -> std::sync::MutexGuard<'_, std::collections::BTreeMap<(String, &'static str), Stats>> {
+        self.stats
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Middleware for Timing {
+    fn handle(&self, request: Request, next: Next<'_>) -> crate::Result<Response> {
+        let key = (request.method.to_string(), request.endpoint);
+        let start = std::time::Instant::now();
+
+        let result = next.run(request);
+
+        let elapsed = start.elapsed();
+        let mut stats = self.lock();
+        let stats = stats.entry(key).or_default();
+        stats.calls += 1;
+        stats.total += elapsed;
+        stats.max = stats.max.max(elapsed);
+        if !result.as_ref().is_ok_and(Response::is_success) {
+            stats.errors += 1;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::middleware::{Chain, Next, Request, Response};
+
+    fn request() -> Request {
+        Request {
+            method: reqwest::Method::POST,
+            endpoint: "/checkouts/{id}",
+            url: "https://api.sumup.com/checkouts/42".to_string(),
+            headers: reqwest::header::HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    fn transport(request: Request) -> crate::Result<Response> {
+        let trace = request
+            .headers
+            .get("x-trace")
+            .and_then(|x| x.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(Response {
+            status: 201,
+            body: trace,
+
+            ..Default::default()
+        })
+    }
+
+    fn tag(name: &'static str) -> impl Fn(Request, Next<'_>) -> crate::Result<Response> {
+        move |mut request: Request, next: Next<'_>| {
+            let trace = request
+                .headers
+                .get("x-trace")
+                .and_then(|x| x.to_str().ok())
+                .map_or(name.to_string(), |x| format!("{x},{name}"));
+            request.headers.insert("x-trace", trace.parse().unwrap());
+
+            let mut response = next.run(request)?;
+            response.body.push_str(&format!(";{name}"));
+
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn chain() -> crate::Result {
+        let chain = Chain::new().with(tag("a")).with(tag("b"));
+
+        let response = chain.run(request(), transport)?;
+
+        assert_eq!(response.body, "a,b;b;a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn short_circuit() {
+        let audit = |request: Request, next: Next<'_>| {
+            if request.is_mutating() {
+                return Err(crate::Error::Middleware("read only".into()));
+            }
+
+            next.run(request)
+        };
+        let chain = Chain::new().with(audit);
+
+        let err = chain.run(request(), transport).unwrap_err();
+
+        assert_eq!(err.to_string(), "read only");
+    }
+
+    #[test]
+    fn timing() -> crate::Result {
+        let timing = std::sync::Arc::new(crate::middleware::Timing::new());
+        let mut chain = Chain::new().with(crate::middleware::Logging::default());
+        chain.push(timing.clone());
+
+        chain.run(request(), transport)?;
+        chain.run(request(), transport)?;
+
+        let stats = timing.stats();
+        let stats = &stats[&("POST".to_string(), "/checkouts/{id}")];
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.errors, 0);
+        assert!(stats.max <= stats.total);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1033 @@
+/** Credentials accepted by the token endpoint, see [`Server::config`] */
+pub const CLIENT_ID: &str = "mock-client-id";
+pub const CLIENT_SECRET: &str = "mock-client-secret";
+pub const USERNAME: &str = "merchant@example.org";
+pub const PASSWORD: &str = "mock-password";
+
+/** Identifiers of the seeded resources */
+pub const MERCHANT_CODE: &str = "MH4H92C7";
+/** Paid checkout of [`TRANSACTION_ID`] */
+pub const CHECKOUT_ID: &str = "4e425463-3e1b-431d-83fa-1e51c2925e99";
+pub const CHECKOUT_REFERENCE: &str = "ORDER-1";
+pub const CUSTOMER_ID: &str = "63827d30b0ccda0004457bc5";
+/** Active card of [`CUSTOMER_ID`] */
+pub const CARD_TOKEN: &str = "bcfc8e5f-3b47-4cb9-854b-3b7a4cce7be3";
+/** Transaction of `fixtures/transaction.json`, paid out in [`PAYOUT_REFERENCE`] */
+pub const TRANSACTION_ID: &str = "4ba9fd34-b8e5-4b67-b2f2-6d2bda5f0a8c";
+pub const TRANSACTION_CODE: &str = "TEENSK4W2K";
+pub const TRANSACTION_INTERNAL_ID: u32 = 1234;
+pub const PAYOUT_REFERENCE: &str = "PO-0107";
+
+/**
+ * In-process HTTP server emulating the SumUp API with in-memory state.
+ *
+ * Each server starts from the same seeded merchant, checkout, customer, transaction and payout,
+ * and serves the token, checkout, customer, merchant, payouts, subaccount and transaction
+ * endpoints used by [`crate::SumUp`]. It stops when dropped.
+ */
+pub struct Server {
+    url: String,
+    server: std::sync::Arc<tiny_http::Server>,
+    state: std::sync::Arc<std::sync::Mutex<State>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /**
+     * Listens on a free local port.
+     */
+    pub fn start() -> crate::Result<Self> {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(std::io::Error::other)?;
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| std::io::Error::other("mock server is not listening on TCP"))?;
+
+        let server = std::sync::Arc::new(server);
+        let state = std::sync::Arc::new(std::sync::Mutex::new(State::new()));
+
+        let thread = {
+            let server = server.clone();
+            let state = state.clone();
+
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    serve(&state, request);
+                }
+            })
+        };
+
+        Ok(Self {
+            url: format!("http://{address}"),
+            server,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /**
+     * Password flow configuration pointing to this server.
+     */
+    #[must_use]
+    pub fn config(&self) -> crate::Config {
+        crate::Config {
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            grant_type: crate::config::GrantType::Password,
+            username: Some(USERNAME.to_string()),
+            password: Some(PASSWORD.to_string()),
+            base_url: Some(self.url.clone()),
+
+            ..Default::default()
+        }
+    }
+
+    /**
+     * Adds a payout line to the ones served.
+     */
+    pub fn push_payout(&self, payout: crate::Payout) {
+        self.state().payouts.push(payout);
+    }
+
+    /**
+     * Adds a transaction to the ones served, changed at its timestamp.
+     */
+    pub fn push_transaction(&self, transaction: crate::Transaction) {
+        let changed = transaction.timestamp.clone();
+        self.state().transactions.push((transaction, changed));
+    }
+
+    /**
+     * Received requests, as `METHOD /path?query`.
+     */
+    #[must_use]
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server").field("url", &self.url).finish()
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.server.unblock();
+
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn lock(state: &std::sync::Mutex<State>) -> std::sync::MutexGuard<'_, State> {
+    state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn serve(state: &std::sync::Mutex<State>, mut request: tiny_http::Request) {
+    let method = request.method().as_str().to_uppercase();
+    let url = request.url().to_string();
+    let authorization = request
+        .headers()
+        .iter()
+        .find(|x| x.field.equiv("Authorization"))
+        .map(|x| x.value.as_str().to_string());
+
+    let mut content = String::new();
+    let reply = match std::io::Read::read_to_string(request.as_reader(), &mut content) {
+        Ok(_) if content.trim().is_empty() => lock(state).handle(
+            &method,
+            &url,
+            authorization.as_deref(),
+            &serde_json::Value::Null,
+        ),
+        Ok(_) => match serde_json::from_str(&content) {
+            Ok(body) => lock(state).handle(&method, &url, authorization.as_deref(), &body),
+            Err(err) => Reply::error(400, "INVALID", &format!("Invalid JSON: {err}")),
+        },
+        Err(err) => Reply::error(400, "INVALID", &err.to_string()),
+    };
+
+    let response = match reply.body {
+        Some(body) => tiny_http::Response::from_string(body.to_string())
+            .with_status_code(reply.status)
+            .with_header(
+                tiny_http::Header::from_bytes("Content-Type", "application/json")
+                    .expect("valid header"),
+            )
+            .boxed(),
+        None => tiny_http::Response::empty(reply.status).boxed(),
+    };
+
+    if let Err(err) = request.respond(response) {
+        log::warn!("Mock server failed to respond: {err}");
+    }
+}
+
+struct Reply {
+    status: u16,
+    body: Option<serde_json::Value>,
+}
+
+impl Reply {
+    fn ok(body: impl serde::Serialize) -> Self {
+        Self::json(200, body)
+    }
+
+    fn created(body: impl serde::Serialize) -> Self {
+        Self::json(201, body)
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: 204,
+            body: None,
+        }
+    }
+
+    fn json(status: u16, body: impl serde::Serialize) -> Self {
+        match serde_json::to_value(body) {
+            Ok(body) => Self {
+                status,
+                body: Some(body),
+            },
+            Err(err) => Self::error(500, "INTERNAL_ERROR", &err.to_string()),
+        }
+    }
+
+    fn error(status: u16, error_code: &str, message: &str) -> Self {
+        Self {
+            status,
+            body: Some(serde_json::json!({
+                "error_code": error_code,
+                "message": message,
+            })),
+        }
+    }
+
+    fn invalid(param: &str, message: &str) -> Self {
+        Self {
+            status: 400,
+            body: Some(serde_json::json!({
+                "error_code": "INVALID",
+                "message": message,
+                "param": param,
+            })),
+        }
+    }
+
+    fn oauth(status: u16, error: &str, description: &str) -> Self {
+        Self {
+            status,
+            body: Some(serde_json::json!({
+                "error": error,
+                "error_description": description,
+            })),
+        }
+    }
+
+    fn not_found(what: &str) -> Self {
+        Self::error(404, "NOT_FOUND", &format!("{what} not found"))
+    }
+}
+
+struct State {
+    sequence: u64,
+    access_tokens: std::collections::HashSet<String>,
+    refresh_tokens: std::collections::HashSet<String>,
+    profile: serde_json::Value,
+    personal_profile: serde_json::Value,
+    bank_accounts: serde_json::Value,
+    settings: serde_json::Value,
+    checkouts: Vec<serde_json::Value>,
+    customers: Vec<serde_json::Value>,
+    /** `(customer_id, instrument)` */
+    instruments: Vec<(String, crate::PaymentInstrument)>,
+    subaccounts: Vec<serde_json::Value>,
+    payouts: Vec<crate::Payout>,
+    /** `(transaction, last change)`, the last change is used for `changes_since` */
+    transactions: Vec<(crate::Transaction, String)>,
+    requests: Vec<String>,
+}
+
+impl State {
+    fn new() -> Self {
+        let address = serde_json::json!({
+            "address_line1": "6 boulevard Saint-Germain",
+            "city": "Paris",
+            "country": "FR",
+            "post_code": "75006",
+            "landline": null,
+        });
+
+        let transaction: crate::Transaction =
+            serde_json::from_str(include_str!("../fixtures/transaction.json"))
+                .expect("valid transaction fixture");
+
+        Self {
+            sequence: 0,
+            access_tokens: std::collections::HashSet::new(),
+            refresh_tokens: std::collections::HashSet::new(),
+            profile: serde_json::json!({
+                "merchant_code": MERCHANT_CODE,
+                "company_name": "Café de Flore",
+                "legal_type": {
+                    "id": 1,
+                    "full_description": "Sole trader",
+                    "description": "Sole trader",
+                    "sole_trader": true,
+                },
+                "merchant_category_code": "5814",
+                "address": address,
+                "business_owners": [],
+                "doing_business_as": {
+                    "business_name": "Café de Flore",
+                    "email": USERNAME,
+                    "dynamic_descriptor": "CAFE DE FLORE",
+                    "address": address,
+                },
+                "locale": "fr-FR",
+                "complete": true,
+                "extdev": false,
+                "country": "FR",
+                "default_currency": "EUR",
+            }),
+            personal_profile: serde_json::json!({
+                "first_name": "John",
+                "last_name": "Doe",
+                "date_of_birth": "1980-01-01",
+                "mobile_phone": null,
+                "address": address,
+                "national_id": "",
+                "complete": true,
+            }),
+            bank_accounts: serde_json::json!([{
+                "bank_code": "37040044",
+                "account_number": "DE89370400440532013000",
+                "account_holder_name": "John Doe",
+                "status": "ACTIVE",
+                "primary": true,
+                "created_at": "2020-01-01T00:00:00.000Z",
+                "bank_name": "Commerzbank",
+            }]),
+            settings: serde_json::json!({
+                "tax_enabled": true,
+                "payout_type": "BANK_ACCOUNT",
+                "payout_period": "DAILY",
+                "payout_on_demand_available": false,
+                "payout_on_demand": false,
+                "printers_enabled": false,
+                "payout_instrument": "BANK_ACCOUNT",
+                "moto_payment": "UNAVAILABLE",
+                "checkout_payments": "ENABLED",
+                "daily_payout_email": false,
+                "monthly_payout_email": true,
+                "gross_settlement": false,
+                "bank_account_change_blocked": false,
+                "operator_personal_profile_name": false,
+                "operator_personal_profile_date_of_birth": false,
+                "operator_personal_profile_address": false,
+                "operator_personal_profile_note": false,
+            }),
+            checkouts: vec![serde_json::json!({
+                "id": CHECKOUT_ID,
+                "amount": transaction.amount,
+                "currency": transaction.currency,
+                "checkout_reference": CHECKOUT_REFERENCE,
+                "merchant_code": MERCHANT_CODE,
+                "status": "PAID",
+                "date": transaction.timestamp,
+                "transaction_id": TRANSACTION_ID,
+                "transaction_code": TRANSACTION_CODE,
+            })],
+            customers: vec![serde_json::json!({
+                "customer_id": CUSTOMER_ID,
+                "personal_detail": {
+                    "first_name": "John",
+                    "last_name": "Doe",
+                    "email": "john.doe@example.org",
+                },
+            })],
+            instruments: vec![(
+                CUSTOMER_ID.to_string(),
+                crate::PaymentInstrument {
+                    token: CARD_TOKEN.to_string(),
+                    active: true,
+                    ty: "card".to_string(),
+                    card: crate::MaskedCard {
+                        last_4_digits: "4242".to_string(),
+                        ty: "VISA".to_string(),
+                    },
+                    mandate: None,
+                    created_at: Some("2021-01-01T00:00:00.000Z".to_string()),
+                },
+            )],
+            subaccounts: Vec::new(),
+            payouts: vec![crate::Payout {
+                amount: transaction.amount,
+                currency: transaction.currency.clone(),
+                date: "2021-01-07".to_string(),
+                fee: 0.19,
+                id: 1,
+                reference: PAYOUT_REFERENCE.to_string(),
+                status: "SUCCESSFUL".to_string(),
+                transaction_code: TRANSACTION_CODE.to_string(),
+                ty: "PAYOUT".to_string(),
+            }],
+            transactions: vec![(transaction.clone(), transaction.timestamp.clone())],
+            requests: Vec::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> String {
+        self.sequence += 1;
+
+        format!("00000000-0000-4000-8000-{:012x}", self.sequence)
+    }
+
+    fn handle(
+        &mut self,
+        method: &str,
+        url: &str,
+        authorization: Option<&str>,
+        body: &serde_json::Value,
+    ) -> Reply {
+        self.requests.push(format!("{method} {url}"));
+
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = Query::parse(query);
+        let versioned = path.starts_with("/v0.1/");
+        let segments = path
+            .trim_start_matches("/v0.1")
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+
+        if segments == ["token"] {
+            return if method == "POST" {
+                self.token(body)
+            } else {
+                Reply::error(405, "INVALID", "Method not allowed")
+            };
+        }
+
+        let authorized = authorization
+            .and_then(|x| x.strip_prefix("Bearer "))
+            .is_some_and(|x| self.access_tokens.contains(x));
+        if !authorized {
+            return Reply::error(401, "NOT_AUTHORIZED", "Unauthorized");
+        }
+
+        match (method, segments.as_slice()) {
+            ("GET", ["me"]) => Reply::ok(serde_json::json!({
+                "account": {
+                    "username": USERNAME,
+                    "type": "normal",
+                },
+                "personal_profile": self.personal_profile,
+                "merchant_profile": self.profile,
+                "requirements": [],
+                "verifications": [],
+                "is_migrated_payleven_br": false,
+                "signup_time": "2020-01-01T00:00:00.000Z",
+                "details_submitted": true,
+            })),
+            ("GET", ["me", "personal-profile"]) => Reply::ok(&self.personal_profile),
+            ("GET", ["me", "merchant-profile"]) => Reply::ok(&self.profile),
+            ("PUT", ["me", "merchant-profile"]) => {
+                merge(&mut self.profile, body);
+                Reply::ok(&self.profile)
+            }
+            ("GET", ["me", "merchant-profile", "doing-business-as"]) => {
+                Reply::ok(&self.profile["doing_business_as"])
+            }
+            ("PUT", ["me", "merchant-profile", "doing-business-as"]) => {
+                merge(&mut self.profile["doing_business_as"], body);
+                Reply::ok(&self.profile["doing_business_as"])
+            }
+            ("GET", ["me", "merchant-profile", "bank-accounts"]) => Reply::ok(&self.bank_accounts),
+            ("GET", ["me", "merchant-profile", "settings"]) => Reply::ok(&self.settings),
+            ("GET", ["merchants", merchant_code, "payment-methods"]) => {
+                if *merchant_code == MERCHANT_CODE {
+                    Reply::ok(serde_json::json!([{"id": "card"}, {"id": "apple_pay"}]))
+                } else {
+                    Reply::not_found("Merchant")
+                }
+            }
+            ("POST", ["checkouts"]) => self.checkout_create(body),
+            ("GET", ["checkouts"]) => {
+                let reference = query.get("checkout_reference").unwrap_or_default();
+                let mut checkouts = self
+                    .checkouts
+                    .iter()
+                    .filter(|x| x["checkout_reference"] == reference);
+
+                if versioned {
+                    Reply::ok(checkouts.collect::<Vec<_>>())
+                } else {
+                    checkouts
+                        .next()
+                        .map_or_else(|| Reply::not_found("Checkout"), Reply::ok)
+                }
+            }
+            ("GET", ["checkouts", id]) => self
+                .checkout(id)
+                .map_or_else(|| Reply::not_found("Checkout"), |x| Reply::ok(&*x)),
+            ("PUT", ["checkouts", id]) => self.checkout_pay(id, body),
+            ("DELETE", ["checkouts", id]) => match self.checkout(id) {
+                Some(checkout) if checkout["status"] == "PENDING" => {
+                    checkout["status"] = "EXPIRED".into();
+                    Reply::ok(&*checkout)
+                }
+                Some(_) => Reply::error(409, "CHECKOUT_PROCESSED", "Checkout is already processed"),
+                None => Reply::not_found("Checkout"),
+            },
+            ("POST", ["customers"]) => {
+                let Some(customer_id) = body["customer_id"].as_str() else {
+                    return Reply::invalid("customer_id", "Missing customer id");
+                };
+                if self.customer(customer_id).is_some() {
+                    return Reply::error(409, "CUSTOMER_ALREADY_EXISTS", "Customer already exists");
+                }
+
+                self.customers.push(body.clone());
+                Reply::created(body)
+            }
+            ("GET", ["customers", id]) => self
+                .customer(id)
+                .map_or_else(|| Reply::not_found("Customer"), |x| Reply::ok(&*x)),
+            ("PUT", ["customers", id]) => match self.customer(id) {
+                Some(customer) => {
+                    customer["personal_detail"] = body["personal_detail"].clone();
+                    Reply::ok(&*customer)
+                }
+                None => Reply::not_found("Customer"),
+            },
+            ("GET", ["customers", id, "payment-instruments"]) => {
+                if self.customer(id).is_none() {
+                    return Reply::not_found("Customer");
+                }
+
+                Reply::ok(
+                    self.instruments
+                        .iter()
+                        .filter(|(customer_id, x)| customer_id == id && x.active)
+                        .map(|(_, x)| x)
+                        .collect::<Vec<_>>(),
+                )
+            }
+            ("POST", ["customers", id, "payment-instruments"]) => {
+                self.instrument_create(id, &body["card"])
+            }
+            ("DELETE", ["customers", id, "payment-instruments", token]) => {
+                match self
+                    .instruments
+                    .iter_mut()
+                    .find(|(customer_id, x)| customer_id == id && x.token == *token)
+                {
+                    Some((_, instrument)) => {
+                        instrument.active = false;
+                        Reply::no_content()
+                    }
+                    None => Reply::not_found("Payment instrument"),
+                }
+            }
+            ("GET", ["me", "financials", "payouts"]) => {
+                let payouts = self
+                    .payouts
+                    .iter()
+                    .filter(|x| query.in_period(&x.date))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                Reply::ok(query.page(payouts, |x| x.date.clone()))
+            }
+            ("GET", ["me", "financials", "transactions"]) => {
+                let transactions = self
+                    .transactions
+                    .iter()
+                    .map(|(x, _)| x)
+                    .filter(|x| query.in_period(&x.timestamp))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                Reply::ok(query.page(transactions, |x| x.timestamp.clone()))
+            }
+            ("POST", ["me", "accounts"]) => self.subaccount_create(body),
+            ("GET", ["me", "accounts"]) => Reply::ok(&self.subaccounts),
+            ("PUT", ["me", "accounts", id]) => {
+                let now = crate::date::now_rfc3339();
+
+                match self.subaccounts.iter_mut().find(|x| x["id"] == *id) {
+                    Some(subaccount) => {
+                        if let Some(username) = body["username"].as_str() {
+                            subaccount["username"] = username.into();
+                        }
+                        subaccount["updated_at"] = now.into();
+                        Reply::ok(&*subaccount)
+                    }
+                    None => Reply::not_found("Subaccount"),
+                }
+            }
+            ("DELETE", ["me", "accounts", id]) => {
+                match self.subaccounts.iter_mut().find(|x| x["id"] == *id) {
+                    Some(subaccount) => {
+                        subaccount["disabled"] = true.into();
+                        Reply::ok(&*subaccount)
+                    }
+                    None => Reply::not_found("Subaccount"),
+                }
+            }
+            ("GET", ["me", "transactions"]) => {
+                let found = self.transactions.iter().map(|(x, _)| x).find(|x| {
+                    query.get("id") == Some(x.id.as_str())
+                        || query.get("internal_id") == Some(x.internal_id.as_str())
+                        || query.get("transaction_code") == Some(x.transaction_code.as_str())
+                });
+
+                found.map_or_else(|| Reply::not_found("Transaction"), Reply::ok)
+            }
+            ("GET", ["me", "transactions", "history"]) => self.history(&query),
+            ("POST", ["me", "refund", id]) => self.refund(id, body),
+            ("GET", ["receipts", id]) => {
+                let found = self
+                    .transactions
+                    .iter()
+                    .map(|(x, _)| x)
+                    .find(|x| x.id == *id || x.transaction_code == *id || x.internal_id == *id);
+
+                found.map_or_else(
+                    || Reply::not_found("Receipt"),
+                    |x| Reply::ok(serde_json::json!({ "transaction_data": x })),
+                )
+            }
+            _ => Reply::not_found(&format!("{method} {path}")),
+        }
+    }
+
+    fn token(&mut self, body: &serde_json::Value) -> Reply {
+        if body["client_id"] != CLIENT_ID || body["client_secret"] != CLIENT_SECRET {
+            return Reply::oauth(401, "invalid_client", "Unknown client or wrong secret");
+        }
+
+        let granted = match body["grant_type"].as_str() {
+            Some("password") => body["username"] == USERNAME && body["password"] == PASSWORD,
+            Some("authorization_code") => body["code"].as_str().is_some_and(|x| !x.is_empty()),
+            Some("client_credentials") => true,
+            Some("refresh_token") => body["refresh_token"]
+                .as_str()
+                .is_some_and(|x| self.refresh_tokens.remove(x)),
+            _ => return Reply::oauth(400, "unsupported_grant_type", "Unknown grant type"),
+        };
+        if !granted {
+            return Reply::oauth(400, "invalid_grant", "Invalid credentials");
+        }
+
+        self.sequence += 1;
+        let access_token = format!("mock-access-token-{}", self.sequence);
+        let refresh_token = format!("mock-refresh-token-{}", self.sequence);
+        self.access_tokens.insert(access_token.clone());
+        self.refresh_tokens.insert(refresh_token.clone());
+
+        Reply::ok(serde_json::json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "scope": body["scope"].as_str().unwrap_or("payments transactions.history user.profile_readonly"),
+            "refresh_token": refresh_token,
+        }))
+    }
+
+    fn checkout(&mut self, id: &str) -> Option<&mut serde_json::Value> {
+        self.checkouts.iter_mut().find(|x| x["id"] == id)
+    }
+
+    fn checkout_create(&mut self, body: &serde_json::Value) -> Reply {
+        for param in ["amount", "currency", "checkout_reference", "merchant_code"] {
+            if body[param].is_null() {
+                return Reply::error(400, "MISSING", &format!("Missing {param}"));
+            }
+        }
+        if body["merchant_code"] != MERCHANT_CODE {
+            return Reply::invalid("merchant_code", "Unknown merchant code");
+        }
+        if self
+            .checkouts
+            .iter()
+            .any(|x| x["checkout_reference"] == body["checkout_reference"])
+        {
+            return Reply::error(
+                409,
+                "DUPLICATED_CHECKOUT",
+                "Checkout with this checkout reference already exists",
+            );
+        }
+
+        let mut checkout = body.clone();
+        checkout["id"] = self.next_id().into();
+        checkout["status"] = "PENDING".into();
+        checkout["date"] = crate::date::now_rfc3339().into();
+        self.checkouts.push(checkout.clone());
+
+        Reply::created(checkout)
+    }
+
+    fn checkout_pay(&mut self, id: &str, body: &serde_json::Value) -> Reply {
+        let Some(checkout) = self.checkout(id).cloned() else {
+            return Reply::not_found("Checkout");
+        };
+        if checkout["status"] != "PENDING" {
+            return Reply::error(400, "CHECKOUT_PROCESSED", "Checkout is already processed");
+        }
+
+        let customer_id = body["customer_id"].as_str().unwrap_or_default();
+        let token = body["token"].as_str().unwrap_or_default();
+        let Some(card) = self
+            .instruments
+            .iter()
+            .find(|(x, instrument)| {
+                x == customer_id && instrument.token == token && instrument.active
+            })
+            .map(|(_, instrument)| instrument.card.clone())
+        else {
+            return Reply::invalid("token", "Unknown payment instrument");
+        };
+
+        let now = crate::date::now_rfc3339();
+        let mut transaction = self.transactions[0].0.clone();
+        transaction.id = self.next_id();
+        transaction.transaction_code = format!("TMOCK{:05}", self.sequence);
+        transaction.internal_id = (TRANSACTION_INTERNAL_ID as u64 + self.sequence).to_string();
+        transaction.amount = checkout["amount"].as_f64().unwrap_or_default() as f32;
+        transaction.currency = checkout["currency"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        transaction.timestamp.clone_from(&now);
+        transaction.local_time.clone_from(&now);
+        transaction.status = crate::Status::Successful;
+        transaction.payment_type = crate::PaymentType::Ecom;
+        transaction.simple_payment_type = crate::PaymentType::Ecom;
+        transaction.entry_mode = "CUSTOMER_ENTRY".to_string();
+        transaction.card = crate::TransactionCard {
+            last_4_digits: card.last_4_digits,
+            ty: card.ty,
+        };
+        transaction.vat_amount = 0.;
+        transaction.tip_amount = 0.;
+        transaction.tax_enabled = false;
+        transaction.products.clear();
+        transaction.vat_rates.clear();
+        transaction.transaction_events.clear();
+        transaction.events.clear();
+        transaction.links.clear();
+        transaction.product_summary.clear();
+
+        let Some(checkout) = self.checkout(id) else {
+            return Reply::not_found("Checkout");
+        };
+        checkout["status"] = "PAID".into();
+        checkout["transaction_id"] = transaction.id.clone().into();
+        checkout["transaction_code"] = transaction.transaction_code.clone().into();
+        let checkout = checkout.clone();
+
+        self.transactions.push((transaction, now));
+
+        Reply::ok(checkout)
+    }
+
+    fn customer(&mut self, id: &str) -> Option<&mut serde_json::Value> {
+        self.customers.iter_mut().find(|x| x["customer_id"] == id)
+    }
+
+    fn instrument_create(&mut self, customer_id: &str, card: &serde_json::Value) -> Reply {
+        if self.customer(customer_id).is_none() {
+            return Reply::not_found("Customer");
+        }
+
+        let field = |name: &str| card[name].as_str().unwrap_or_default().to_string();
+        let card = crate::NewCard {
+            name: field("name"),
+            number: field("number").into(),
+            expiry_year: field("expiry_year"),
+            expiry_month: field("expiry_month"),
+            cvv: field("cvv").into(),
+            zip_code: field("zip_code"),
+        };
+
+        let brand = match crate::card::validate(&card) {
+            Ok(brand) => brand,
+            Err(errors) => return Reply::invalid(&errors[0].field.to_string(), "Invalid card"),
+        };
+        let number = card.number.replace([' ', '-'], "");
+
+        let instrument = crate::PaymentInstrument {
+            token: self.next_id(),
+            active: true,
+            ty: "card".to_string(),
+            card: crate::MaskedCard {
+                last_4_digits: number[number.len() - 4..].to_string(),
+                ty: serde_json::to_value(brand)
+                    .ok()
+                    .and_then(|x| x.as_str().map(str::to_uppercase))
+                    .unwrap_or_default(),
+            },
+            mandate: None,
+            created_at: Some(crate::date::now_rfc3339()),
+        };
+        self.instruments
+            .push((customer_id.to_string(), instrument.clone()));
+
+        Reply::created(instrument)
+    }
+
+    fn subaccount_create(&mut self, body: &serde_json::Value) -> Reply {
+        let Some(username) = body["username"].as_str() else {
+            return Reply::error(400, "MISSING", "Missing username");
+        };
+        if body["password"].as_str().is_none_or(str::is_empty) {
+            return Reply::error(400, "MISSING", "Missing password");
+        }
+        if self.subaccounts.iter().any(|x| x["username"] == username) {
+            return Reply::invalid("username", "Username already taken");
+        }
+
+        let now = crate::date::now_rfc3339();
+        let subaccount = serde_json::json!({
+            "account_type": "operator",
+            "created_at": now,
+            "disabled": false,
+            "id": self.next_id(),
+            "permissions": {
+                "create_moto_payments": false,
+                "create_referral": false,
+                "full_transaction_history_view": false,
+                "refund_transactions": false,
+            },
+            "updated_at": now,
+            "username": username,
+        });
+        self.subaccounts.push(subaccount.clone());
+
+        Reply::created(subaccount)
+    }
+
+    /**
+     * Ordered by timestamp then id, `oldest_ref` and `newest_ref` are cursors: only the
+     * transactions after, or before, the referenced one are returned. Times are exclusive.
+     */
+    fn history(&self, query: &Query) -> Reply {
+        let key = |x: &crate::Transaction| format!("{} {}", x.timestamp, x.id);
+        let cursor = |name: &str| {
+            query.get(name).map(|reference| {
+                self.transactions
+                    .iter()
+                    .find(|(x, _)| x.id == reference)
+                    .map(|(x, _)| key(x))
+                    .unwrap_or_default()
+            })
+        };
+        let oldest_ref = cursor("oldest_ref");
+        let newest_ref = cursor("newest_ref");
+
+        let transactions = self
+            .transactions
+            .iter()
+            .filter(|(x, _)| query.in_period(&x.timestamp))
+            .filter(|(_, changed)| {
+                query
+                    .get("changes_since")
+                    .is_none_or(|since| changed.as_str() >= since)
+            })
+            .filter(|(x, _)| {
+                query
+                    .get("oldest_time")
+                    .is_none_or(|time| x.timestamp.as_str() > time)
+            })
+            .filter(|(x, _)| {
+                query
+                    .get("newest_time")
+                    .is_none_or(|time| x.timestamp.as_str() < time)
+            })
+            .filter(|(x, _)| oldest_ref.as_ref().is_none_or(|cursor| key(x) > *cursor))
+            .filter(|(x, _)| newest_ref.as_ref().is_none_or(|cursor| key(x) < *cursor))
+            .filter(|(x, _)| {
+                let statuses = query.all("statuse");
+                statuses.is_empty() || statuses.contains(&x.status.to_string().as_str())
+            })
+            .filter(|(x, _)| {
+                let payment_types = query.all("payment_type");
+                payment_types.is_empty()
+                    || payment_types.contains(&x.payment_type.to_string().as_str())
+            })
+            .map(|(x, _)| crate::Transaction {
+                // History items carry no events
+                events: Vec::new(),
+                transaction_events: Vec::new(),
+
+                ..x.clone()
+            })
+            .collect::<Vec<_>>();
+
+        Reply::ok(query.page(transactions, key))
+    }
+
+    fn refund(&mut self, id: &str, body: &serde_json::Value) -> Reply {
+        let now = crate::date::now_rfc3339();
+
+        let Some((transaction, changed)) = self.transactions.iter_mut().find(|(x, _)| x.id == id)
+        else {
+            return Reply::not_found("Transaction");
+        };
+
+        let refundable = transaction.refundable().amount;
+        let amount = body["amount"].as_f64().map_or(refundable, |x| x as f32);
+
+        if amount <= 0. || amount - refundable > 0.005 {
+            return Reply::invalid("amount", "The amount exceeds the refundable amount");
+        }
+
+        let event = crate::Event {
+            id: transaction.events.iter().map(|x| x.id).max().unwrap_or(0) + 1,
+            transaction_id: transaction.id.clone(),
+            ty: "REFUND".to_string(),
+            status: "PENDING".to_string(),
+            amount,
+            timestamp: now.clone(),
+            installment_number: 1,
+
+            ..Default::default()
+        };
+        transaction.events.push(event);
+
+        if transaction.refundable().amount <= 0.005 {
+            transaction.status = crate::Status::Refunded;
+        }
+        *changed = now;
+
+        Reply::no_content()
+    }
+}
+
+/**
+ * Merges the fields of `patch` into `target`, objects are merged recursively.
+ */
+fn merge(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(query: &str) -> Self {
+        let pairs = query
+            .split('&')
+            .filter_map(|x| x.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        Self(pairs)
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(x, _)| x == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn all(&self, key: &str) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(x, _)| x == key)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    /** `start_date` and `end_date` are inclusive days */
+    fn in_period(&self, timestamp: &str) -> bool {
+        let day = timestamp.get(..10).unwrap_or(timestamp);
+
+        self.get("start_date").is_none_or(|x| day >= x)
+            && self.get("end_date").is_none_or(|x| day <= x)
+    }
+
+    /** Sorts by `order` then keeps the first `limit` items */
+    fn page<T>(&self, mut items: Vec<T>, key: impl Fn(&T) -> String) -> Vec<T> {
+        items.sort_by_key(|x| key(x));
+        if self.get("order") == Some("desc") {
+            items.reverse();
+        }
+
+        let limit = self.get("limit").and_then(|x| x.parse().ok()).unwrap_or(10);
+        items.truncate(limit);
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn server() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        let transaction = api
+            .transactions()
+            .find_by_code(crate::mock::TRANSACTION_CODE)?;
+        assert_eq!(transaction.id, crate::mock::TRANSACTION_ID);
+
+        let refund = api.transactions().refund(&transaction.id, 9.)?;
+        assert_eq!(refund.event.map(|x| x.amount), Some(9.));
+        assert!(matches!(
+            api.transactions().refund(&transaction.id, 1.),
+            Err(crate::Error::RefundExceeded { .. })
+        ));
+
+        let err = api.checkout().find_by_id("unknown").unwrap_err();
+        assert!(err.is_not_found());
+
+        assert!(server.requests().contains(&format!(
+            "GET /v0.1/me/transactions?transaction_code={}",
+            crate::mock::TRANSACTION_CODE
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unauthorized() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let config = crate::Config {
+            password: Some("wrong".to_string()),
+
+            ..server.config()
+        };
+
+        let err = crate::SumUp::from(config).unwrap_err();
+        assert!(err.is_auth());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,441 @@
+/** Amounts closer than this are considered equal */
+const TOLERANCE: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    /** The transaction events explain both the amount and the fee */
+    Matched,
+    /** The transaction was found but its events don't add up to the payout line */
+    PartiallyMatched,
+    /** No transaction matches the payout line */
+    Unexplained,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Item {
+    pub payout: crate::Payout,
+    pub transaction_id: Option<String>,
+    pub state: State,
+    /** Amount expected from the transaction events */
+    pub expected_amount: f32,
+    /** Fee expected from the transaction events */
+    pub expected_fee: f32,
+    /** Refunds and chargebacks deducted from the transaction */
+    pub deducted: f32,
+}
+
+impl Item {
+    /** Payout line amount not explained by the transaction events */
+    #[must_use]
+    pub fn difference(&self) -> f32 {
+        net(&self.payout) - (self.expected_amount - self.expected_fee - self.deducted)
+    }
+}
+
+/**
+ * One bank payout, all the payout lines sharing the same `reference`.
+ */
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct PayoutReport {
+    pub reference: String,
+    pub date: String,
+    pub currency: String,
+    pub items: Vec<Item>,
+    pub amount: f32,
+    pub fee: f32,
+    /** Sum of the payout lines, fees deducted */
+    pub net: f32,
+    pub matched: usize,
+    pub partially_matched: usize,
+    pub unexplained: usize,
+    /** Part of the net amount not explained by transactions */
+    pub unexplained_amount: f32,
+}
+
+impl PayoutReport {
+    #[must_use]
+    pub fn is_reconciled(&self) -> bool {
+        self.partially_matched == 0 && self.unexplained == 0
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct Report {
+    pub payouts: Vec<PayoutReport>,
+    /** Successful transactions which don't appear in any payout */
+    pub unpaid_transactions: Vec<String>,
+}
+
+impl Report {
+    #[must_use]
+    pub fn is_reconciled(&self) -> bool {
+        self.payouts.iter().all(PayoutReport::is_reconciled)
+    }
+}
+
+/**
+ * Explains payouts with the transactions they pay out.
+ *
+ * Each payout line is matched with a transaction by `transaction_code`, falling back to
+ * `reference`. Lines of type `PAYOUT` are compared with the transaction `PAYOUT` events (amount,
+ * `fee_amount` and the `deducted_amount`/`deducted_fee_amount` withheld for refunds and
+ * chargebacks), `REFUND_DEDUCTION` and `CHARGE_BACK_DEDUCTION` lines with the `REFUND` and
+ * `CHARGE_BACK` events, other lines with no event. Only the events whose `transaction_id` is the
+ * transaction id are taken into account.
+ */
+#[must_use]
+pub fn reconcile(payouts: &[crate::Payout], transactions: &[crate::Transaction]) -> Report {
+    let by_code = transactions
+        .iter()
+        .map(|x| (x.transaction_code.as_str(), x))
+        .collect::<std::collections::HashMap<_, _>>();
+
+    let mut reports = Vec::<PayoutReport>::new();
+    let mut paid = std::collections::HashSet::new();
+
+    for payout in payouts {
+        let transaction = by_code
+            .get(payout.transaction_code.as_str())
+            .or_else(|| by_code.get(payout.reference.as_str()))
+            .copied();
+
+        let item = item(payout, transaction);
+
+        if let Some(transaction) = transaction {
+            paid.insert(transaction.id.as_str());
+        }
+
+        let index = reports
+            .iter()
+            .position(|x| x.reference == payout.reference)
+            .unwrap_or_else(|| {
+                reports.push(PayoutReport {
+                    reference: payout.reference.clone(),
+                    date: payout.date.clone(),
+                    currency: payout.currency.clone(),
+
+                    ..Default::default()
+                });
+                reports.len() - 1
+            });
+        let report = &mut reports[index];
+
+        report.amount += payout.amount;
+        report.fee += payout.fee;
+        report.net += net(payout);
+
+        match item.state {
+            State::Matched => report.matched += 1,
+            State::PartiallyMatched => {
+                report.partially_matched += 1;
+                report.unexplained_amount += item.difference();
+            }
+            State::Unexplained => {
+                report.unexplained += 1;
+                report.unexplained_amount += net(payout);
+            }
+        }
+
+        report.items.push(item);
+    }
+
+    let unpaid_transactions = transactions
+        .iter()
+        .filter(|x| matches!(x.status, crate::Status::Successful))
+        .filter(|x| !paid.contains(x.id.as_str()))
+        .map(|x| x.id.clone())
+        .collect();
+
+    Report {
+        payouts: reports,
+        unpaid_transactions,
+    }
+}
+
+fn item(payout: &crate::Payout, transaction: Option<&crate::Transaction>) -> Item {
+    let Some(transaction) = transaction else {
+        return Item {
+            payout: payout.clone(),
+            transaction_id: None,
+            state: State::Unexplained,
+            expected_amount: 0.,
+            expected_fee: 0.,
+            deducted: 0.,
+        };
+    };
+
+    let is_payout = payout.ty == "PAYOUT";
+    let event_type = event_type(&payout.ty);
+    let events = transaction
+        .events
+        .iter()
+        .filter(|x| x.transaction_id == transaction.id)
+        .filter(|x| Some(x.ty.as_str()) == event_type);
+
+    let mut item = Item {
+        payout: payout.clone(),
+        transaction_id: Some(transaction.id.clone()),
+        state: State::PartiallyMatched,
+        expected_amount: 0.,
+        expected_fee: 0.,
+        deducted: 0.,
+    };
+
+    for event in events {
+        if is_payout {
+            item.expected_amount += event.amount;
+            item.expected_fee += event.fee_amount;
+            item.deducted += event.deducted_amount + event.deducted_fee_amount;
+        } else {
+            item.expected_amount += event.amount.abs();
+        }
+    }
+
+    if !is_payout {
+        // Deductions are reported as positive or negative amounts depending on the payout type
+        item.expected_amount = item.expected_amount.copysign(payout.amount);
+    }
+
+    if item.difference().abs() < TOLERANCE && (payout.fee - item.expected_fee).abs() < TOLERANCE {
+        item.state = State::Matched;
+    }
+
+    item
+}
+
+/**
+ * Type of the transaction events explaining a payout line of type `ty`.
+ */
+fn event_type(ty: &str) -> Option<&'static str> {
+    match ty {
+        "PAYOUT" => Some("PAYOUT"),
+        "REFUND_DEDUCTION" => Some("REFUND"),
+        "CHARGE_BACK_DEDUCTION" => Some("CHARGE_BACK"),
+        _ => None,
+    }
+}
+
+fn net(payout: &crate::Payout) -> f32 {
+    payout.amount - payout.fee
+}
+
+/**
+ * Fetches the payouts of a period, the transactions they reference and the transactions history
+ * of the same period, to report the transactions not paid out yet.
+ *
+ * Sales of the last `payout_delay_days` of the period are paid out after it, they are left out of
+ * the unpaid transactions.
+ */
+#[derive(Clone, Debug)]
+pub struct Reconciler<'a> {
+    payouts: crate::services::Payouts<'a>,
+    transactions: crate::services::Transactions<'a>,
+    pub payout_delay_days: i64,
+}
+
+impl<'a> Reconciler<'a> {
+    #[must_use]
+    pub fn new(
+        payouts: crate::services::Payouts<'a>,
+        transactions: crate::services::Transactions<'a>,
+    ) -> Self {
+        Self {
+            payouts,
+            transactions,
+            payout_delay_days: 7,
+        }
+    }
+
+    pub fn run(&self, filter: &crate::services::payouts::Filter) -> crate::Result<Report> {
+        let payouts = self
+            .payouts
+            .pages(filter)
+            .collect::<crate::Result<Vec<_>>>()?
+            .concat();
+        let mut transactions = Vec::<crate::Transaction>::new();
+
+        // The history lacks the events, the paid out transactions are fetched one by one
+        for payout in &payouts {
+            if payout.transaction_code.is_empty()
+                || transactions
+                    .iter()
+                    .any(|x| x.transaction_code == payout.transaction_code)
+            {
+                continue;
+            }
+
+            transactions.push(self.transactions.find_by_code(&payout.transaction_code)?);
+        }
+
+        let cutoff = crate::date::Date::parse(&filter.end_date)
+            .map(|x| crate::date::Date::from_days(x.days() - self.payout_delay_days).to_string());
+        let history = crate::services::transactions::Filter {
+            start_date: filter.start_date.clone(),
+            end_date: cutoff.unwrap_or_else(|| filter.end_date.clone()),
+            limit: Some(100),
+
+            ..Default::default()
+        };
+        if history.start_date <= history.end_date {
+            for page in self.transactions.pages(&history) {
+                for transaction in page? {
+                    if transactions.iter().all(|x| x.id != transaction.id) {
+                        transactions.push(transaction);
+                    }
+                }
+            }
+        }
+
+        Ok(reconcile(&payouts, &transactions))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn transaction() -> crate::Transaction {
+        serde_json::from_str(include_str!("../fixtures/transaction.json")).unwrap()
+    }
+
+    fn payout(transaction_code: &str, amount: f32, fee: f32) -> crate::Payout {
+        crate::Payout {
+            amount,
+            currency: "EUR".to_string(),
+            date: "2021-01-07".to_string(),
+            fee,
+            id: 1,
+            reference: "PAYOUT-1".to_string(),
+            status: "SUCCESSFUL".to_string(),
+            transaction_code: transaction_code.to_string(),
+            ty: "PAYOUT".to_string(),
+        }
+    }
+
+    #[test]
+    fn reconcile() {
+        let transactions = vec![transaction()];
+        let payouts = vec![payout("TEENSK4W2K", 9.76, 0.19), payout("UNKNOWN", 5., 0.1)];
+
+        let report = crate::reconciliation::reconcile(&payouts, &transactions);
+
+        assert_eq!(report.payouts.len(), 1);
+        assert!(report.unpaid_transactions.is_empty());
+
+        let payout = &report.payouts[0];
+        assert_eq!(payout.matched, 1);
+        assert_eq!(payout.unexplained, 1);
+        assert!((payout.unexplained_amount - 4.9).abs() < 0.001);
+        assert!(!report.is_reconciled());
+    }
+
+    #[test]
+    fn partially_matched() {
+        let transactions = vec![transaction()];
+        let payouts = vec![payout("TEENSK4W2K", 9., 0.19)];
+
+        let report = crate::reconciliation::reconcile(&payouts, &transactions);
+
+        let item = &report.payouts[0].items[0];
+        assert_eq!(item.state, crate::reconciliation::State::PartiallyMatched);
+        assert!((item.difference() + 0.76).abs() < 0.001);
+    }
+
+    #[test]
+    fn deduction() {
+        let mut transaction = transaction();
+        let mut refund = transaction.events[0].clone();
+        refund.ty = "REFUND".to_string();
+        refund.amount = 2.;
+        transaction.events.push(refund);
+
+        let mut payout = payout("TEENSK4W2K", -2., 0.);
+        payout.ty = "REFUND_DEDUCTION".to_string();
+
+        let report = crate::reconciliation::reconcile(&[payout.clone()], &[transaction.clone()]);
+        assert_eq!(
+            report.payouts[0].items[0].state,
+            crate::reconciliation::State::Matched
+        );
+
+        // Events without type explain nothing
+        transaction.events.last_mut().unwrap().ty = String::new();
+        let report = crate::reconciliation::reconcile(&[payout.clone()], &[transaction.clone()]);
+        assert_eq!(
+            report.payouts[0].items[0].state,
+            crate::reconciliation::State::PartiallyMatched
+        );
+
+        payout.ty = String::new();
+        let report = crate::reconciliation::reconcile(&[payout], &[transaction]);
+        assert_eq!(
+            report.payouts[0].items[0].state,
+            crate::reconciliation::State::PartiallyMatched
+        );
+    }
+
+    #[test]
+    fn unpaid() {
+        let transactions = vec![transaction()];
+
+        let report = crate::reconciliation::reconcile(&[], &transactions);
+
+        assert_eq!(report.unpaid_transactions, vec![transactions[0].id.clone()]);
+        assert!(report.is_reconciled());
+    }
+
+    #[test]
+    fn run() -> crate::Result {
+        let api = crate::test::api()?;
+
+        let filter = crate::services::payouts::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2021-01-12".to_string(),
+            limit: Some(20),
+            descending_order: false,
+        };
+
+        let report = crate::reconciliation::Reconciler::new(api.payouts(), api.transactions())
+            .run(&filter)?;
+        if report.payouts.is_empty() && report.unpaid_transactions.is_empty() {
+            log::warn!("Empty response");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_unpaid() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        for (id, timestamp) in [
+            ("unpaid", "2021-01-03T10:00:00.000Z"),
+            ("recent", "2021-01-11T10:00:00.000Z"),
+        ] {
+            server.push_transaction(crate::Transaction {
+                id: id.to_string(),
+                transaction_code: id.to_uppercase(),
+                timestamp: timestamp.to_string(),
+                status: crate::Status::Successful,
+
+                ..transaction()
+            });
+        }
+
+        let filter = crate::services::payouts::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2021-01-12".to_string(),
+            limit: Some(1),
+            descending_order: false,
+        };
+
+        let report = crate::reconciliation::Reconciler::new(api.payouts(), api.transactions())
+            .run(&filter)?;
+
+        assert_eq!(report.payouts.len(), 1);
+        assert_eq!(report.payouts[0].matched, 1);
+        assert_eq!(report.unpaid_transactions, vec!["unpaid".to_string()]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+/** Replacement of the secrets */
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/** JSON keys whose values are secrets, compared case insensitively */
+const SECRET_KEYS: &[&str] = &[
+    "access_token",
+    "client_secret",
+    "code",
+    "cvv",
+    "password",
+    "refresh_token",
+    "token",
+];
+
+/**
+ * [`SECRET_KEYS`] without the payment instrument `token`, which identifies a stored card but
+ * doesn't give access to the account
+ */
+const CREDENTIAL_KEYS: &[&str] = &[
+    "access_token",
+    "client_secret",
+    "code",
+    "cvv",
+    "password",
+    "refresh_token",
+];
+
+/** JSON keys whose values are card numbers, only the last 4 digits are kept */
+const CARD_NUMBER_KEYS: &[&str] = &["number", "card_number", "pan"];
+
+/**
+ * Keeps the last 4 characters of a card number.
+ */
+pub(crate) fn card_number(number: &str) -> String {
+    let chars = number.chars().collect::<Vec<_>>();
+
+    if chars.len() <= 4 {
+        return REDACTED.to_string();
+    }
+
+    let last = chars[chars.len() - 4..].iter().collect::<String>();
+    format!("{}{last}", "*".repeat(chars.len() - 4))
+}
+
+pub(crate) fn option(secret: Option<&String>) -> Option<&'static str> {
+    secret.map(|_| REDACTED)
+}
+
+/**
+ * Replaces the secrets of a JSON document, at any depth.
+ */
+pub(crate) fn json(value: &mut serde_json::Value) {
+    redact(value, SECRET_KEYS);
+}
+
+/**
+ * Replaces the credentials and card data of a JSON document, at any depth, keeping the
+ * identifiers needed to replay it.
+ */
+pub(crate) fn credentials(value: &mut serde_json::Value) {
+    redact(value, CREDENTIAL_KEYS);
+}
+
+fn redact(value: &mut serde_json::Value, secret_keys: &[&str]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+
+                if secret_keys.contains(&key.as_str()) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else if CARD_NUMBER_KEYS.contains(&key.as_str()) && value.is_string() {
+                    *value =
+                        serde_json::Value::String(card_number(value.as_str().unwrap_or_default()));
+                } else {
+                    redact(value, secret_keys);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            values.iter_mut().for_each(|x| redact(x, secret_keys));
+        }
+        _ => (),
+    }
+}
+
+/**
+ * Serializes a raw body with its secrets replaced, for logging. Non-JSON bodies are logged as is.
+ */
+pub(crate) fn body(body: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            json(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn payload() {
+        let card = serde_json::json!({
+            "type": "card",
+            "card": {
+                "name": "John Doe",
+                "number": "4242424242424242",
+                "expiry_year": "2030",
+                "expiry_month": "01",
+                "cvv": "123",
+                "zip_code": "75001",
+            },
+            "password": null,
+        });
+
+        assert_eq!(
+            super::body(&card.to_string()),
+            r#"{"card":{"cvv":"[REDACTED]","expiry_month":"01","expiry_year":"2030","name":"John Doe","number":"************4242","zip_code":"75001"},"password":null,"type":"card"}"#
+        );
+    }
+
+    #[test]
+    fn body() {
+        assert_eq!(
+            super::body(r#"[{"access_token": "secret", "token_type": "Bearer"}]"#),
+            r#"[{"access_token":"[REDACTED]","token_type":"Bearer"}]"#
+        );
+        assert_eq!(super::body("<html>"), "<html>");
+    }
+
+    #[test]
+    fn credentials() {
+        let mut value = serde_json::json!({
+            "access_token": "secret",
+            "token": "card-token",
+            "card": {"number": "4242424242424242", "cvv": "123"},
+        });
+
+        super::credentials(&mut value);
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "access_token": "[REDACTED]",
+                "token": "card-token",
+                "card": {"number": "************4242", "cvv": "[REDACTED]"},
+            })
+        );
+    }
+
+    #[test]
+    fn debug() {
+        let config = crate::Config {
+            client_secret: "client-secret".to_string(),
+            password: Some("hunter2".to_string()),
+
+            ..Default::default()
+        };
+        let card = crate::NewCard {
+            number: "4242424242424242".to_string().into(),
+            cvv: "123".to_string().into(),
+
+            ..Default::default()
+        };
+        let access_token = crate::AccessToken {
+            access_token: "access-token".to_string(),
+
+            ..Default::default()
+        };
+
+        let debug = format!("{config:?} {card:?} {access_token:?}");
+
+        assert!(!debug.contains("client-secret"));
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("4242424242424242"));
+        assert!(!debug.contains("123"));
+        assert!(!debug.contains("access-token"));
+    }
+
+    #[test]
+    fn card_number() {
+        assert_eq!(super::card_number("4242"), super::REDACTED);
+        assert_eq!(super::card_number("4000056655665556"), "************5556");
+    }
+}
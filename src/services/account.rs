@@ -13,6 +13,10 @@ impl<'a> Account<'a> {
     /**
      * <https://developer.sumup.com/docs/api/retrieve-an-account/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Account::get", skip_all)
+    )]
     pub fn get(&self) -> crate::Result<crate::Account> {
         self.api.account_get(self.access_token)
     }
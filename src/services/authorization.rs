@@ -10,6 +10,10 @@ impl<'a> Authorization<'a> {
         Self { api, config }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Authorization::token", skip_all)
+    )]
     pub fn token(&self) -> crate::Result<crate::AccessToken> {
         let token = if let Some(access_token) = &self.config.access_token {
             crate::AccessToken {
@@ -13,6 +13,10 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/get-available-payment-methods/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::payment_methods", skip_all)
+    )]
     pub fn payment_methods(
         &self,
         merchant_code: &str,
@@ -26,6 +30,10 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/create-a-checkout/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::create", skip_all)
+    )]
     pub fn create(&self, checkout: &crate::Checkout) -> crate::Result {
         self.api.checkout_create(checkout, self.access_token)
     }
@@ -33,6 +41,10 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-checkouts/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::list", skip_all)
+    )]
     pub fn list(&self, checkout_reference: &str) -> crate::Result<Vec<crate::Checkout>> {
         self.api
             .checkout_list(checkout_reference, self.access_token)
@@ -41,10 +53,18 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/retrieve-a-checkout/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::find_by_id", skip_all)
+    )]
     pub fn find_by_id(&self, id: &str) -> crate::Result<crate::Checkout> {
         self.api.checkout_get(id, self.access_token)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::find_by_reference_id", skip_all)
+    )]
     pub fn find_by_reference_id(&self, reference_id: &str) -> crate::Result<crate::Checkout> {
         self.api
             .checkout_reference_id(reference_id, self.access_token)
@@ -53,6 +73,10 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/deactivate-a-checkout/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::delete", skip_all)
+    )]
     pub fn delete(&self, id: &str) -> crate::Result {
         self.api.checkout_delete(id, self.access_token)
     }
@@ -60,6 +84,10 @@ impl<'a> Checkout<'a> {
     /**
      * <https://developer.sumup.com/docs/api/process-a-checkout/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::pay", skip_all)
+    )]
     pub fn pay(
         &self,
         id: &str,
@@ -76,6 +104,127 @@ impl<'a> Checkout<'a> {
 
         self.api.checkout_update(id, payload, self.access_token)
     }
+
+    /**
+     * Polls the checkout until it leaves the `PENDING` status, then fetches its transaction. A
+     * missing or empty status is still pending.
+     *
+     * Fails with [`crate::Error::Timeout`] once `options.timeout` is elapsed and with
+     * [`crate::Error::Cancelled`] as soon as `options.cancel` is set.
+     */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Checkout::wait_until_settled", skip_all)
+    )]
+    pub fn wait_until_settled(&self, id: &str, options: &WaitOptions) -> crate::Result<Settled> {
+        let deadline = std::time::Instant::now() + options.timeout;
+        let mut interval = options.interval;
+
+        loop {
+            options.check_cancel()?;
+
+            let checkout = self.find_by_id(id)?;
+            let status = checkout.status.clone().unwrap_or_default();
+
+            if !matches!(status.as_str(), "PENDING" | "") {
+                let transaction = match &checkout.transaction_id {
+                    Some(transaction_id) => Some(
+                        crate::services::Transactions::new(self.api, self.access_token)
+                            .find_by_id(transaction_id)?,
+                    ),
+                    None => None,
+                };
+
+                return Ok(Settled {
+                    status,
+                    checkout,
+                    transaction,
+                });
+            }
+
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(crate::Error::Timeout(format!(
+                    "checkout {id} still pending after {:?}",
+                    options.timeout
+                )));
+            }
+
+            options.sleep(interval.min(remaining))?;
+            interval = options.next_interval(interval);
+        }
+    }
+}
+
+/**
+ * Poll intervals of [`Checkout::wait_until_settled`]: `interval`, multiplied by `backoff` after
+ * each poll, up to `max_interval`. A `backoff` below 1 or NaN is taken as 1.
+ */
+#[derive(Clone, Debug)]
+pub struct WaitOptions {
+    pub interval: std::time::Duration,
+    pub max_interval: std::time::Duration,
+    pub backoff: f32,
+    pub timeout: std::time::Duration,
+    /** Set it from another thread to stop waiting */
+    pub cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            interval: std::time::Duration::from_millis(500),
+            max_interval: std::time::Duration::from_secs(5),
+            backoff: 1.5,
+            timeout: std::time::Duration::from_secs(120),
+            cancel: None,
+        }
+    }
+}
+
+impl WaitOptions {
+    fn next_interval(&self, interval: std::time::Duration) -> std::time::Duration {
+        let backoff = self.backoff.max(1.);
+
+        std::time::Duration::try_from_secs_f32(interval.as_secs_f32() * backoff)
+            .map_or(self.max_interval, |x| x.min(self.max_interval))
+    }
+
+    fn check_cancel(&self) -> crate::Result {
+        match &self.cancel {
+            Some(cancel) if cancel.load(std::sync::atomic::Ordering::Relaxed) => {
+                Err(crate::Error::Cancelled)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Sleeps by steps of 50 ms at most, to notice cancellation quickly.
+     */
+    fn sleep(&self, duration: std::time::Duration) -> crate::Result {
+        let step = std::time::Duration::from_millis(50);
+        let end = std::time::Instant::now() + duration;
+
+        loop {
+            self.check_cancel()?;
+
+            let remaining = end.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Ok(());
+            }
+
+            std::thread::sleep(remaining.min(step));
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Settled {
+    /** `PAID`, `FAILED`, `EXPIRED`… */
+    pub status: String,
+    pub checkout: crate::Checkout,
+    pub transaction: Option<crate::Transaction>,
 }
 
 #[cfg(test)]
@@ -120,7 +269,7 @@ mod test {
     fn list() -> crate::Result {
         let api = crate::test::api()?;
 
-        let checkouts = api.checkout().list("1")?;
+        let checkouts = api.checkout().list(crate::mock::CHECKOUT_REFERENCE)?;
         if checkouts.is_empty() {
             log::warn!("Empty response");
         }
@@ -132,7 +281,7 @@ mod test {
     fn find_by_id() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.checkout().find_by_id("1")?;
+        api.checkout().find_by_id(crate::mock::CHECKOUT_ID)?;
 
         Ok(())
     }
@@ -141,16 +290,109 @@ mod test {
     fn find_by_reference_id() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.checkout().find_by_reference_id("1")?;
+        api.checkout()
+            .find_by_reference_id(crate::mock::CHECKOUT_REFERENCE)?;
 
         Ok(())
     }
 
+    fn pending(api: &crate::SumUp, reference: &str) -> crate::Result<String> {
+        let profile = api.merchant().profile()?;
+
+        let checkout = crate::Checkout {
+            amount: 2.2,
+            currency: "EUR".to_string(),
+            checkout_reference: reference.to_string(),
+            merchant_code: profile.merchant_code,
+
+            ..Default::default()
+        };
+        api.checkout().create(&checkout)?;
+
+        let checkout = api.checkout().find_by_reference_id(reference)?;
+
+        Ok(checkout.id.unwrap_or_default())
+    }
+
     #[test]
     fn delete() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.checkout().delete("1")?;
+        let id = pending(&api, "delete")?;
+        api.checkout().delete(&id)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn next_interval() {
+        let options = crate::services::checkout::WaitOptions::default();
+
+        let mut interval = options.interval;
+        for _ in 0..3 {
+            interval = options.next_interval(interval);
+        }
+
+        assert_eq!(interval, std::time::Duration::from_micros(1_687_500));
+
+        for _ in 0..10 {
+            interval = options.next_interval(interval);
+        }
+
+        assert_eq!(interval, options.max_interval);
+
+        for backoff in [-2., 0.5, f32::NAN] {
+            let options = crate::services::checkout::WaitOptions {
+                backoff,
+
+                ..Default::default()
+            };
+            assert_eq!(options.next_interval(options.interval), options.interval);
+        }
+
+        for backoff in [f32::INFINITY, f32::MAX] {
+            let options = crate::services::checkout::WaitOptions {
+                backoff,
+
+                ..Default::default()
+            };
+            assert_eq!(
+                options.next_interval(options.interval),
+                options.max_interval
+            );
+        }
+    }
+
+    #[test]
+    fn cancel() {
+        let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let options = crate::services::checkout::WaitOptions {
+            cancel: Some(cancel.clone()),
+
+            ..Default::default()
+        };
+
+        assert!(options.sleep(std::time::Duration::from_millis(1)).is_ok());
+
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        assert!(matches!(
+            options.sleep(std::time::Duration::from_secs(60)),
+            Err(crate::Error::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn wait_until_settled() -> crate::Result {
+        let api = crate::test::api()?;
+
+        let options = crate::services::checkout::WaitOptions {
+            timeout: std::time::Duration::from_secs(5),
+
+            ..Default::default()
+        };
+        api.checkout()
+            .wait_until_settled(crate::mock::CHECKOUT_ID, &options)?;
 
         Ok(())
     }
@@ -159,7 +401,9 @@ mod test {
     fn pay() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.checkout().pay("1", "1", "1", None)?;
+        let id = pending(&api, "pay")?;
+        api.checkout()
+            .pay(&id, crate::mock::CUSTOMER_ID, crate::mock::CARD_TOKEN, None)?;
 
         Ok(())
     }
@@ -13,6 +13,10 @@ impl<'a> Customer<'a> {
     /**
      * <https://developer.sumup.com/docs/api/create-a-customer/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::create", skip_all)
+    )]
     pub fn create(&self, customer: &crate::Customer) -> crate::Result {
         self.api.customer_create(customer, self.access_token)
     }
@@ -20,6 +24,10 @@ impl<'a> Customer<'a> {
     /**
      * <https://developer.sumup.com/docs/api/update-a-customer/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::update", skip_all)
+    )]
     pub fn update(&self, customer: &crate::Customer) -> crate::Result<crate::Customer> {
         self.api
             .customer_update(&customer.customer_id, customer, self.access_token)
@@ -28,6 +36,10 @@ impl<'a> Customer<'a> {
     /**
      * <https://developer.sumup.com/docs/api/retrieve-a-customer/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::get", skip_all)
+    )]
     pub fn get(&self, id: &str) -> crate::Result<crate::Customer> {
         self.api.customer_get(id, self.access_token)
     }
@@ -35,23 +47,36 @@ impl<'a> Customer<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-payment-instruments/>
      */
-    pub fn payment_instruments(&self, customer_id: &str) -> crate::Result<Vec<crate::Card>> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::payment_instruments", skip_all)
+    )]
+    pub fn payment_instruments(
+        &self,
+        customer_id: &str,
+    ) -> crate::Result<Vec<crate::PaymentInstrument>> {
         self.api
             .customer_payment_instruments(customer_id, self.access_token)
     }
 
     /**
      * <https://developer.sumup.com/docs/api/create-a-payment-instrument/>
+     *
+     * The card is checked with [`crate::card::validate`] first, failing with
+     * [`crate::Error::InvalidCard`] without calling the API.
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::create_payment_instruments", skip_all)
+    )]
     pub fn create_payment_instruments(
         &self,
         customer_id: &str,
-        card: &crate::Card,
-    ) -> crate::Result<crate::Card> {
-        let payload = serde_json::json!({
-            "type": "card",
-            "card": card,
-        });
+        card: &crate::NewCard,
+    ) -> crate::Result<crate::PaymentInstrument> {
+        card.validate().map_err(crate::Error::InvalidCard)?;
+
+        let payload = PaymentInstrumentPayload { ty: "card", card };
         self.api
             .customer_create_payment_instruments(customer_id, payload, self.access_token)
     }
@@ -59,12 +84,26 @@ impl<'a> Customer<'a> {
     /**
      * <https://developer.sumup.com/docs/api/deactivate-a-payment-instrument/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Customer::delete_payment_instruments", skip_all)
+    )]
     pub fn delete_payment_instruments(&self, customer_id: &str, card_token: &str) -> crate::Result {
         self.api
             .customer_delete_payment_instruments(customer_id, card_token, self.access_token)
     }
 }
 
+/**
+ * Borrows the card, so the number and the CVV are not copied before being serialized.
+ */
+#[derive(serde::Serialize)]
+struct PaymentInstrumentPayload<'c> {
+    #[serde(rename = "type")]
+    ty: &'static str,
+    card: &'c crate::NewCard,
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -100,18 +139,43 @@ mod test {
         let api = crate::test::api()?;
         let api_customer = api.customer();
 
-        let card = crate::Card {
+        let card = crate::NewCard {
             name: "FIRSTNAME LASTNAME".to_string(),
-            number: "1234567890123456".to_string(),
-            expiry_year: "2023".to_string(),
+            number: "4242424242424242".to_string().into(),
+            expiry_year: "2030".to_string(),
             expiry_month: "01".to_string(),
-            cvv: "123".to_string(),
+            cvv: "123".to_string().into(),
             zip_code: "12345".to_string(),
         };
 
-        api_customer.create_payment_instruments("63827d30b0ccda0004457bc5", &card)?;
-        let cards = api_customer.payment_instruments("63827d30b0ccda0004457bc5")?;
-        api_customer.delete_payment_instruments("63827d30b0ccda0004457bc5", &cards[0].name)?;
+        let instrument =
+            api_customer.create_payment_instruments("63827d30b0ccda0004457bc5", &card)?;
+        let instruments = api_customer.payment_instruments("63827d30b0ccda0004457bc5")?;
+        assert!(instruments.iter().any(|x| x.token == instrument.token));
+        api_customer.delete_payment_instruments("63827d30b0ccda0004457bc5", &instrument.token)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn payment_instrument() -> crate::Result {
+        let instrument: crate::PaymentInstrument = serde_json::from_str(
+            r#"{
+                "token": "bcfc8e5f-3b47-4cb9-854b-3b7a4cce7be3",
+                "active": true,
+                "type": "card",
+                "card": {"last_4_digits": "3456", "type": "VISA"},
+                "mandate": {"type": "recurrent", "status": "active", "merchant_code": "MH4H92C7"},
+                "created_at": "2021-03-30T10:06:07.000+00:00"
+            }"#,
+        )?;
+
+        assert_eq!(instrument.token, "bcfc8e5f-3b47-4cb9-854b-3b7a4cce7be3");
+        assert_eq!(instrument.card.last_4_digits, "3456");
+        assert_eq!(
+            instrument.mandate.map(|x| x.status).as_deref(),
+            Some("active")
+        );
 
         Ok(())
     }
@@ -10,10 +10,18 @@ impl<'a> Merchant<'a> {
         Self { api, access_token }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::profile", skip_all)
+    )]
     pub fn profile(&self) -> crate::Result<crate::Profile> {
         self.api.profile_get(self.access_token)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::update_profile", skip_all)
+    )]
     pub fn update_profile(&self, profile: &crate::Profile) -> crate::Result {
         self.api.profile_update(profile, self.access_token)
     }
@@ -21,10 +29,18 @@ impl<'a> Merchant<'a> {
     /**
      * <https://developer.sumup.com/docs/api/retrieve-dba/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::doing_business_as", skip_all)
+    )]
     pub fn doing_business_as(&self) -> crate::Result<crate::DoingBusinessAs> {
         self.api.profile_doing_business_as_get(self.access_token)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::update_doing_business_as", skip_all)
+    )]
     pub fn update_doing_business_as(
         &self,
         dba: &crate::DoingBusinessAs,
@@ -36,6 +52,10 @@ impl<'a> Merchant<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-bank-accounts/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::bank_accounts", skip_all)
+    )]
     pub fn bank_accounts(&self) -> crate::Result<Vec<crate::BankAccount>> {
         self.api.profile_bank_accounts(self.access_token)
     }
@@ -43,6 +63,10 @@ impl<'a> Merchant<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-settings/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Merchant::settings", skip_all)
+    )]
     pub fn settings(&self) -> crate::Result<crate::Settings> {
         self.api.profile_settings(self.access_token)
     }
@@ -10,21 +10,105 @@ impl<'a> Payouts<'a> {
         Self { api, access_token }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Payouts::payouts", skip_all)
+    )]
     pub fn payouts(&self, filters: &Filter) -> crate::Result<Vec<crate::Payout>> {
         self.api.payouts_list(filters, self.access_token)
     }
 
+    /**
+     * Iterates over the payouts page by page, the next page starts at the date of the last payout
+     * of the previous one.
+     *
+     * The API has no cursor within a day: when a full page only holds payouts already seen, as
+     * happens with more than `limit` lines on the same date, it is fetched again with twice the
+     * limit.
+     */
+    #[must_use]
+    pub fn pages(&self, filter: &Filter) -> Pages<'a> {
+        Pages {
+            payouts: self.clone(),
+            filter: filter.clone(),
+            seen: std::collections::HashSet::new(),
+            done: false,
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Payouts::transactions", skip_all)
+    )]
     pub fn transactions(&self, filters: &Filter) -> crate::Result<Vec<crate::Transaction>> {
         self.api.transactions_list(filters, self.access_token)
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Pages<'a> {
+    payouts: Payouts<'a>,
+    filter: Filter,
+    seen: std::collections::HashSet<u32>,
+    done: bool,
+}
+
+impl Iterator for Pages<'_> {
+    type Item = crate::Result<Vec<crate::Payout>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let page = match self.payouts.payouts(&self.filter) {
+                Ok(page) => page,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+
+            let limit = self.filter.limit.unwrap_or(10);
+            let is_full = page.len() >= limit as usize;
+            let last = page.last().map(|x| x.date.clone());
+
+            let page = page
+                .into_iter()
+                .filter(|x| self.seen.insert(x.id))
+                .collect::<Vec<_>>();
+
+            if page.is_empty() {
+                if is_full && limit < u32::MAX {
+                    self.filter.limit = Some(limit.saturating_mul(2));
+                    continue;
+                }
+
+                self.done = true;
+                return None;
+            }
+
+            self.done = !is_full;
+            if let Some(last) = last {
+                if self.filter.descending_order {
+                    self.filter.end_date = last;
+                } else {
+                    self.filter.start_date = last;
+                }
+            }
+
+            return Some(Ok(page));
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Filter {
-    start_date: String,
-    end_date: String,
-    limit: Option<u32>,
-    descending_order: bool,
+    pub start_date: String,
+    pub end_date: String,
+    pub limit: Option<u32>,
+    pub descending_order: bool,
 }
 
 #[allow(clippy::to_string_trait_impl)]
@@ -62,6 +146,76 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn pages() -> crate::Result {
+        let api = crate::test::api()?;
+
+        let filter = crate::services::payouts::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2021-01-12".to_string(),
+            limit: Some(1),
+            descending_order: false,
+        };
+
+        let payouts = api.payouts().payouts(&crate::services::payouts::Filter {
+            limit: Some(100),
+
+            ..filter.clone()
+        })?;
+        let pages = api
+            .payouts()
+            .pages(&filter)
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        assert_eq!(pages.concat().len(), payouts.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn pages_same_date() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        for (id, date) in [
+            (2, "2021-01-07"),
+            (3, "2021-01-07"),
+            (4, "2021-01-07"),
+            (5, "2021-01-07"),
+            (6, "2021-01-08"),
+        ] {
+            server.push_payout(crate::Payout {
+                id,
+                date: date.to_string(),
+
+                ..Default::default()
+            });
+        }
+
+        for descending_order in [false, true] {
+            let filter = crate::services::payouts::Filter {
+                start_date: "2021-01-01".to_string(),
+                end_date: "2021-01-12".to_string(),
+                limit: Some(2),
+                descending_order,
+            };
+
+            let mut ids = api
+                .payouts()
+                .pages(&filter)
+                .collect::<crate::Result<Vec<_>>>()?
+                .concat()
+                .iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>();
+            ids.sort_unstable();
+
+            assert_eq!(ids, [1, 2, 3, 4, 5, 6]);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn transactions() -> crate::Result {
         let api = crate::test::api()?;
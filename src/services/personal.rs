@@ -13,6 +13,10 @@ impl<'a> Personal<'a> {
     /**
      * <https://developer.sumup.com/docs/api/retrieve-a-profile/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Personal::get", skip_all)
+    )]
     pub fn get(&self) -> crate::Result<crate::PersonalProfile> {
         self.api.personal_get(self.access_token)
     }
@@ -13,6 +13,10 @@ impl<'a> Subaccounts<'a> {
     /**
      * <https://developer.sumup.com/docs/api/create-a-subaccount/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Subaccounts::create", skip_all)
+    )]
     pub fn create(&self, email: &str, password: &str) -> crate::Result<crate::SubAccount> {
         let payload = serde_json::json!({
             "username": email,
@@ -25,6 +29,10 @@ impl<'a> Subaccounts<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-subaccounts/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Subaccounts::list", skip_all)
+    )]
     pub fn list(&self) -> crate::Result<Vec<crate::SubAccount>> {
         self.api.subaccounts_list(self.access_token)
     }
@@ -32,6 +40,10 @@ impl<'a> Subaccounts<'a> {
     /**
      * <https://developer.sumup.com/docs/api/update-a-subaccount/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Subaccounts::update", skip_all)
+    )]
     pub fn update(
         &self,
         id: &str,
@@ -54,6 +66,10 @@ impl<'a> Subaccounts<'a> {
     /**
      * <https://developer.sumup.com/docs/api/deactivate-a-subaccount/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Subaccounts::delete", skip_all)
+    )]
     pub fn delete(&self, id: &str) -> crate::Result<crate::SubAccount> {
         self.api.subaccounts_delete(id, self.access_token)
     }
@@ -2,26 +2,53 @@
 pub struct Transactions<'a> {
     api: &'a crate::Api,
     access_token: &'a crate::AccessToken,
+    refund_guard: bool,
 }
 
 impl<'a> Transactions<'a> {
     #[must_use]
     pub fn new(api: &'a crate::Api, access_token: &'a crate::AccessToken) -> Self {
-        Self { api, access_token }
+        Self {
+            api,
+            access_token,
+            refund_guard: false,
+        }
+    }
+
+    /**
+     * Checks [`crate::Transaction::refundable`] before sending refunds, a refund of more than
+     * the refundable amount fails with [`crate::Error::RefundExceeded`] without calling the API.
+     */
+    #[must_use]
+    pub fn refund_guard(mut self, enabled: bool) -> Self {
+        self.refund_guard = enabled;
+        self
     }
 
     /**
      * <https://developer.sumup.com/docs/api/retrieve-a-transaction/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::find_by_id", skip_all)
+    )]
     pub fn find_by_id(&self, id: &str) -> crate::Result<crate::Transaction> {
         self.api.transactions_get(id, self.access_token)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::find_by_internal_id", skip_all)
+    )]
     pub fn find_by_internal_id(&self, internal_id: &str) -> crate::Result<crate::Transaction> {
         self.api
             .transactions_get_by_internal_id(internal_id, self.access_token)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::find_by_code", skip_all)
+    )]
     pub fn find_by_code(&self, code: &str) -> crate::Result<crate::Transaction> {
         self.api.transactions_get_by_code(code, self.access_token)
     }
@@ -29,39 +56,214 @@ impl<'a> Transactions<'a> {
     /**
      * <https://developer.sumup.com/docs/api/list-transactions/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::history", skip_all)
+    )]
     pub fn history(&self, filter: &Filter) -> crate::Result<Vec<crate::Transaction>> {
         self.api.transactions_history(filter, self.access_token)
     }
 
     /**
-     * <https://developer.sumup.com/docs/api/refund-a-transaction/>
+     * Iterates over the history page by page, the next page starts after the last transaction of
+     * the previous one: `oldest_ref` is set to it in ascending order, `newest_ref` in descending
+     * order. Being a cursor on the transaction, not on its time, no transaction sharing the
+     * timestamp of the last one is skipped.
      */
-    pub fn full_refund(&self, id: u32) -> crate::Result {
-        let payload = serde_json::json!({});
+    #[must_use]
+    pub fn pages(&self, filter: &Filter) -> Pages<'a> {
+        Pages {
+            transactions: self.clone(),
+            filter: filter.clone(),
+            seen: std::collections::HashSet::new(),
+            done: false,
+        }
+    }
 
-        self.api.transactions_refund(id, payload, self.access_token)
+    /**
+     * <https://developer.sumup.com/docs/api/refund-a-transaction/>
+     */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::full_refund", skip_all)
+    )]
+    pub fn full_refund(&self, id: &str) -> crate::Result<Refund> {
+        self.send_refund(id, None)
     }
 
     /**
      * <https://developer.sumup.com/docs/api/refund-a-transaction/>
      */
-    pub fn refund(&self, id: u32, amount: f32) -> crate::Result {
-        let payload = serde_json::json!({
-            "amount": amount,
-        });
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::refund", skip_all)
+    )]
+    pub fn refund(&self, id: &str, amount: f32) -> crate::Result<Refund> {
+        self.send_refund(id, Some(amount))
+    }
+
+    /**
+     * Sends the refund then fetches the transaction to return its refund event, the `REFUND`
+     * event of this transaction which wasn't there before the refund.
+     *
+     * When the API rejects the refund, the transaction is fetched to report
+     * [`crate::Error::RefundExceeded`] if the amount is more than what is still refundable. Once
+     * the refund is sent, failing to read the transaction back is only logged: the refund went
+     * through and must not be sent again.
+     */
+    fn send_refund(&self, id: &str, amount: Option<f32>) -> crate::Result<Refund> {
+        // Without the guard, the transaction is only read to recognize the new refund event
+        let before = if self.refund_guard {
+            let transaction = self.find_by_id(id)?;
+            check_refundable(&transaction, amount)?;
+            Some(transaction)
+        } else {
+            self.find_by_id(id)
+                .inspect_err(|err| log::warn!("Refunding {id} without its events: {err}"))
+                .ok()
+        };
+
+        let payload = match amount {
+            Some(amount) => serde_json::json!({
+                "amount": amount,
+            }),
+            None => serde_json::json!({}),
+        };
+
+        match self.api.transactions_refund(id, payload, self.access_token) {
+            Ok(()) => (),
+            Err(err @ crate::Error::Api(_)) => {
+                let Ok(transaction) = self.find_by_id(id) else {
+                    return Err(err);
+                };
+
+                check_refundable(&transaction, amount)?;
+
+                return Err(err);
+            }
+            Err(err) => return Err(err),
+        }
 
-        self.api.transactions_refund(id, payload, self.access_token)
+        let Some(before) = before else {
+            return Ok(Refund {
+                transaction: self.find_by_id(id).ok(),
+                event: None,
+            });
+        };
+
+        let transaction = match self.find_by_id(id) {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                log::warn!("Refund of {id} sent, but the transaction can't be read back: {err}");
+
+                return Ok(Refund {
+                    transaction: Some(before),
+                    event: None,
+                });
+            }
+        };
+        let event = transaction
+            .events
+            .iter()
+            .filter(|x| x.ty == "REFUND" && x.transaction_id == transaction.id)
+            .filter(|x| before.events.iter().all(|known| known.id != x.id))
+            .max_by_key(|x| x.id)
+            .cloned();
+
+        Ok(Refund {
+            transaction: Some(transaction),
+            event,
+        })
     }
 
     /**
      * <https://developer.sumup.com/docs/api/retrieve-receipt-details/>
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "Transactions::receipt", skip_all)
+    )]
     pub fn receipt(&self, id: u32, merchant_id: u32) -> crate::Result<crate::Receipt> {
         self.api
             .transactions_get_receipt(id, merchant_id, self.access_token)
     }
 }
 
+/**
+ * Result of a refund: the refunded transaction, as returned after the refund, and the refund
+ * event it got.
+ */
+#[derive(Clone, Debug)]
+pub struct Refund {
+    /** As before the refund if it couldn't be read back, `None` if it couldn't be read at all */
+    pub transaction: Option<crate::Transaction>,
+    /** `None` when the new event couldn't be told apart */
+    pub event: Option<crate::Event>,
+}
+
+fn check_refundable(transaction: &crate::Transaction, amount: Option<f32>) -> crate::Result {
+    let refundable = transaction.refundable().amount;
+    let requested = amount.unwrap_or(refundable);
+
+    if refundable <= 0. || requested - refundable > 0.005 {
+        return Err(crate::Error::RefundExceeded {
+            requested,
+            refundable,
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct Pages<'a> {
+    transactions: Transactions<'a>,
+    filter: Filter,
+    seen: std::collections::HashSet<String>,
+    done: bool,
+}
+
+impl Iterator for Pages<'_> {
+    type Item = crate::Result<Vec<crate::Transaction>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let page = match self.transactions.history(&self.filter) {
+            Ok(page) => page,
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+
+        let limit = self.filter.limit.unwrap_or(10) as usize;
+        self.done = page.len() < limit;
+
+        if let Some(last) = page.last() {
+            if self.filter.order.as_deref() == Some("desc") {
+                self.filter.newest_ref = Some(last.id.clone());
+            } else {
+                self.filter.oldest_ref = Some(last.id.clone());
+            }
+        }
+
+        let page = page
+            .into_iter()
+            .filter(|x| self.seen.insert(x.id.clone()))
+            .collect::<Vec<_>>();
+
+        if page.is_empty() {
+            self.done = true;
+            return None;
+        }
+
+        Some(Ok(page))
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct Filter {
     pub start_date: String,
@@ -85,12 +287,15 @@ impl ToString for Filter {
     fn to_string(&self) -> String {
         let mut v = Vec::new();
 
-        v.push(format!(
-            "start_date={}&end_date={}&order={}",
-            self.start_date,
-            self.end_date,
-            self.order.as_deref().unwrap_or("asc")
-        ));
+        if !self.start_date.is_empty() {
+            v.push(format!("start_date={}", self.start_date));
+        }
+
+        if !self.end_date.is_empty() {
+            v.push(format!("end_date={}", self.end_date));
+        }
+
+        v.push(format!("order={}", self.order.as_deref().unwrap_or("asc")));
         v.push(format!("limit={}", self.limit.unwrap_or(10)));
 
         if let Some(user_id) = &self.user_id {
@@ -145,7 +350,7 @@ mod test {
     fn find_by_id() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.transactions().find_by_id("1")?;
+        api.transactions().find_by_id(crate::mock::TRANSACTION_ID)?;
 
         Ok(())
     }
@@ -154,7 +359,8 @@ mod test {
     fn find_by_code() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.transactions().find_by_code("1234")?;
+        api.transactions()
+            .find_by_code(crate::mock::TRANSACTION_CODE)?;
 
         Ok(())
     }
@@ -187,11 +393,70 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn pages_same_time() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        let seeded = api.transactions().find_by_id(crate::mock::TRANSACTION_ID)?;
+        for n in 0..4 {
+            server.push_transaction(crate::Transaction {
+                id: format!("same-time-{n}"),
+
+                ..seeded.clone()
+            });
+        }
+        server.push_transaction(crate::Transaction {
+            id: "later".to_string(),
+            timestamp: "2021-06-01T00:00:00.000Z".to_string(),
+
+            ..seeded.clone()
+        });
+
+        for order in ["asc", "desc"] {
+            let filter = crate::services::transactions::Filter {
+                start_date: "2021-01-01".to_string(),
+                end_date: "2021-12-31".to_string(),
+                order: Some(order.to_string()),
+                limit: Some(2),
+
+                ..Default::default()
+            };
+
+            let ids = api
+                .transactions()
+                .pages(&filter)
+                .collect::<crate::Result<Vec<_>>>()?
+                .concat()
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>();
+
+            let mut expected = vec![
+                crate::mock::TRANSACTION_ID.to_string(),
+                "same-time-0".to_string(),
+                "same-time-1".to_string(),
+                "same-time-2".to_string(),
+                "same-time-3".to_string(),
+                "later".to_string(),
+            ];
+            expected.sort_by_key(|x| (x == "later", x.clone()));
+            if order == "desc" {
+                expected.reverse();
+            }
+
+            assert_eq!(ids, expected);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn full_refund() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.transactions().full_refund(1)?;
+        api.transactions()
+            .full_refund(crate::mock::TRANSACTION_ID)?;
 
         Ok(())
     }
@@ -200,7 +465,92 @@ mod test {
     fn refund() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.transactions().refund(1, 1.2)?;
+        api.transactions()
+            .refund(crate::mock::TRANSACTION_ID, 1.2)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn refund_event() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        let first = api
+            .transactions()
+            .refund(crate::mock::TRANSACTION_ID, 1.)?
+            .event
+            .unwrap();
+        let second = api
+            .transactions()
+            .refund(crate::mock::TRANSACTION_ID, 2.)?
+            .event
+            .unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.amount, 1.);
+        assert_eq!(second.amount, 2.);
+        assert_eq!(second.transaction_id, crate::mock::TRANSACTION_ID);
+
+        Ok(())
+    }
+
+    /**
+     * Fails the transaction reads, from the start or once a refund is sent.
+     */
+    struct Unreadable {
+        from_start: bool,
+        refunded: std::sync::atomic::AtomicBool,
+    }
+
+    impl crate::middleware::Middleware for Unreadable {
+        fn handle(
+            &self,
+            request: crate::middleware::Request,
+            next: crate::middleware::Next<'_>,
+        ) -> crate::Result<crate::middleware::Response> {
+            use std::sync::atomic::Ordering;
+
+            if request.method == reqwest::Method::POST && request.endpoint.contains("refund") {
+                self.refunded.store(true, Ordering::Relaxed);
+            } else if request.endpoint.contains("transactions")
+                && (self.from_start || self.refunded.load(Ordering::Relaxed))
+            {
+                return Err(crate::Error::Middleware("unavailable".into()));
+            }
+
+            next.run(request)
+        }
+    }
+
+    #[test]
+    fn refund_unreadable() -> crate::Result {
+        for from_start in [false, true] {
+            let server = crate::mock::Server::start()?;
+            let mut middlewares = crate::middleware::Chain::new();
+            middlewares.push(std::sync::Arc::new(Unreadable {
+                from_start,
+                refunded: std::sync::atomic::AtomicBool::new(false),
+            }));
+            let api = crate::SumUp::from(crate::Config {
+                middlewares,
+
+                ..server.config()
+            })?;
+
+            let refund = api.transactions().refund(crate::mock::TRANSACTION_ID, 1.)?;
+
+            assert_eq!(refund.transaction.is_some(), !from_start);
+            assert!(refund.event.is_none());
+            assert_eq!(
+                server
+                    .requests()
+                    .iter()
+                    .filter(|x| x.contains("refund"))
+                    .count(),
+                1
+            );
+        }
 
         Ok(())
     }
@@ -209,8 +559,60 @@ mod test {
     fn receipt() -> crate::Result {
         let api = crate::test::api()?;
 
-        api.transactions().receipt(1, 1)?;
+        api.transactions()
+            .receipt(crate::mock::TRANSACTION_INTERNAL_ID, 1)?;
 
         Ok(())
     }
+
+    #[test]
+    fn refundable() {
+        let mut transaction: crate::Transaction =
+            serde_json::from_str(include_str!("../../fixtures/transaction.json")).unwrap();
+
+        assert_eq!(transaction.refundable().amount, 9.76);
+        assert!(super::check_refundable(&transaction, Some(9.76)).is_ok());
+
+        let mut refund = transaction.events[0].clone();
+        refund.ty = "REFUND".to_string();
+        refund.status = "FAILED".to_string();
+        refund.amount = 3.96;
+        transaction.events.push(refund.clone());
+
+        assert_eq!(transaction.refundable().refunded, 0.);
+
+        refund.status = "SUCCESSFUL".to_string();
+        transaction.events.push(refund.clone());
+
+        let refundable = transaction.refundable();
+        assert!((refundable.amount - 5.8).abs() < 0.001);
+        assert_eq!(refundable.refunded, 3.96);
+        assert!(matches!(
+            super::check_refundable(&transaction, Some(6.)),
+            Err(crate::Error::RefundExceeded { .. })
+        ));
+
+        refund.ty = "CHARGE_BACK".to_string();
+        transaction.events.push(refund);
+
+        let refundable = transaction.refundable();
+        assert!(refundable.charged_back);
+        assert_eq!(refundable.amount, 0.);
+        assert!(super::check_refundable(&transaction, None).is_err());
+    }
+
+    #[test]
+    fn refundable_transaction_events() {
+        let mut transaction: crate::Transaction =
+            serde_json::from_str(include_str!("../../fixtures/transaction.json")).unwrap();
+        transaction.events.clear();
+
+        let mut refund = transaction.transaction_events[0].clone();
+        refund.event_type = "REFUND".to_string();
+        refund.status = "PENDING".to_string();
+        refund.amount = 1.;
+        transaction.transaction_events.push(refund);
+
+        assert!((transaction.refundable().amount - 8.76).abs() < 0.001);
+    }
 }
@@ -0,0 +1,703 @@
+const SCHEMA: &str = "
+create table if not exists transactions (
+    id text primary key,
+    transaction_code text not null,
+    amount real not null,
+    currency text not null,
+    timestamp text not null,
+    status text not null,
+    payment_type text not null,
+    merchant_code text not null,
+    username text not null,
+    vat_amount real not null,
+    tip_amount real not null,
+    data text not null
+);
+
+create index if not exists transactions_timestamp on transactions (timestamp);
+create index if not exists transactions_code on transactions (transaction_code);
+
+create table if not exists products (
+    transaction_id text not null references transactions (id) on delete cascade,
+    position integer not null,
+    name text not null,
+    price real not null,
+    vat_rate real not null,
+    single_vat_amount real not null,
+    price_with_vat real not null,
+    vat_amount real not null,
+    quantity real not null,
+    total_price real not null,
+    total_with_vat real not null,
+    primary key (transaction_id, position)
+);
+
+create table if not exists events (
+    id integer not null,
+    transaction_id text not null references transactions (id) on delete cascade,
+    type text not null,
+    status text not null,
+    amount real not null,
+    timestamp text not null,
+    fee_amount real not null,
+    installment_number integer not null,
+    deducted_amount real not null,
+    deducted_fee_amount real not null,
+    primary key (transaction_id, id)
+);
+
+create table if not exists transaction_events (
+    id integer not null,
+    transaction_id text not null references transactions (id) on delete cascade,
+    event_type text not null,
+    status text not null,
+    amount real not null,
+    due_date text not null,
+    date text not null,
+    installment_number integer not null,
+    timestamp text not null,
+    primary key (transaction_id, id)
+);
+
+create table if not exists payouts (
+    id integer primary key,
+    amount real not null,
+    currency text not null,
+    date text not null,
+    fee real not null,
+    reference text not null,
+    status text not null,
+    transaction_code text not null,
+    type text not null
+);
+
+create index if not exists payouts_date on payouts (date);
+
+create table if not exists checkouts (
+    id text primary key,
+    checkout_reference text not null,
+    amount real not null,
+    currency text not null,
+    merchant_code text not null,
+    status text,
+    date text,
+    data text not null
+);
+";
+
+/**
+ * Filters applied by [`Store::transactions`], dates are inclusive and compared with the
+ * transaction timestamp.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub statuses: Vec<crate::Status>,
+    pub payment_types: Vec<crate::PaymentType>,
+    pub username: Option<String>,
+}
+
+/**
+ * Local `SQLite` copy of transactions, payouts and checkouts.
+ *
+ * Records are upserted by id, storing the same record twice replaces the previous version.
+ */
+#[derive(Debug)]
+pub struct Store {
+    connection: rusqlite::Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        Self::from(rusqlite::Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> crate::Result<Self> {
+        Self::from(rusqlite::Connection::open_in_memory()?)
+    }
+
+    pub fn from(connection: rusqlite::Connection) -> crate::Result<Self> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self { connection })
+    }
+
+    #[must_use]
+    pub fn connection(&self) -> &rusqlite::Connection {
+        &self.connection
+    }
+
+    pub fn upsert_transaction(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        let tx = self.connection.transaction()?;
+        upsert_transaction(&tx, transaction)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn upsert_payout(&mut self, payout: &crate::Payout) -> crate::Result {
+        upsert_payout(&self.connection, payout)
+    }
+
+    /**
+     * Checkouts without id, as sent to [`crate::services::Checkout::create`], are ignored.
+     */
+    pub fn upsert_checkout(&mut self, checkout: &crate::Checkout) -> crate::Result {
+        upsert_checkout(&self.connection, checkout)
+    }
+
+    /**
+     * Fetches the transactions history page by page and stores it, returns the number of
+     * upserted transactions. Each page is committed on its own.
+     */
+    pub fn populate_transactions(
+        &mut self,
+        service: &crate::services::Transactions,
+        filter: &crate::services::transactions::Filter,
+    ) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for page in service.pages(filter) {
+            let transactions = page?;
+
+            let tx = self.connection.transaction()?;
+            for transaction in &transactions {
+                upsert_transaction(&tx, transaction)?;
+            }
+            tx.commit()?;
+
+            count += transactions.len();
+        }
+
+        Ok(count)
+    }
+
+    /**
+     * Fetches the payouts page by page and stores them, returns the number of upserted payouts.
+     * Each page is committed on its own.
+     */
+    pub fn populate_payouts(
+        &mut self,
+        service: &crate::services::Payouts,
+        filter: &crate::services::payouts::Filter,
+    ) -> crate::Result<usize> {
+        let mut count = 0;
+
+        for page in service.pages(filter) {
+            let payouts = page?;
+
+            let tx = self.connection.transaction()?;
+            for payout in &payouts {
+                upsert_payout(&tx, payout)?;
+            }
+            tx.commit()?;
+
+            count += payouts.len();
+        }
+
+        Ok(count)
+    }
+
+    pub fn populate_checkouts(
+        &mut self,
+        service: &crate::services::Checkout,
+        checkout_reference: &str,
+    ) -> crate::Result<usize> {
+        let checkouts = service.list(checkout_reference)?;
+
+        let tx = self.connection.transaction()?;
+        for checkout in &checkouts {
+            upsert_checkout(&tx, checkout)?;
+        }
+        tx.commit()?;
+
+        Ok(checkouts.len())
+    }
+
+    pub fn transaction(&self, id: &str) -> crate::Result<Option<crate::Transaction>> {
+        use rusqlite::OptionalExtension as _;
+
+        let data = self
+            .connection
+            .query_row("select data from transactions where id = ?1", [id], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()?;
+
+        data.map(|data| serde_json::from_str(&data).map_err(crate::Error::from))
+            .transpose()
+    }
+
+    pub fn transactions(&self, query: &Query) -> crate::Result<Vec<crate::Transaction>> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some(start_date) = &query.start_date {
+            conditions.push("substr(timestamp, 1, 10) >= ?".to_string());
+            params.push(start_date.clone());
+        }
+
+        if let Some(end_date) = &query.end_date {
+            conditions.push("substr(timestamp, 1, 10) <= ?".to_string());
+            params.push(end_date.clone());
+        }
+
+        if !query.statuses.is_empty() {
+            conditions.push(format!(
+                "status in ({})",
+                placeholders(query.statuses.len())
+            ));
+            params.extend(query.statuses.iter().map(ToString::to_string));
+        }
+
+        if !query.payment_types.is_empty() {
+            conditions.push(format!(
+                "payment_type in ({})",
+                placeholders(query.payment_types.len())
+            ));
+            params.extend(query.payment_types.iter().map(ToString::to_string));
+        }
+
+        if let Some(username) = &query.username {
+            conditions.push("username = ?".to_string());
+            params.push(username.clone());
+        }
+
+        let sql = format!(
+            "select data from transactions {} order by timestamp",
+            where_clause(&conditions)
+        );
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement.query_map(rusqlite::params_from_iter(params), |row| {
+            row.get::<_, String>(0)
+        })?;
+
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+
+    /**
+     * Payouts between two inclusive dates.
+     */
+    pub fn payouts(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        status: Option<&str>,
+    ) -> crate::Result<Vec<crate::Payout>> {
+        let mut statement = self.connection.prepare(
+            "select amount, currency, date, fee, id, reference, status, transaction_code, type
+            from payouts
+            where date between ?1 and ?2 and (?3 is null or status = ?3)
+            order by date, id",
+        )?;
+
+        let rows = statement.query_map(rusqlite::params![start_date, end_date, status], |row| {
+            Ok(crate::Payout {
+                amount: row.get(0)?,
+                currency: row.get(1)?,
+                date: row.get(2)?,
+                fee: row.get(3)?,
+                id: row.get(4)?,
+                reference: row.get(5)?,
+                status: row.get(6)?,
+                transaction_code: row.get(7)?,
+                ty: row.get(8)?,
+            })
+        })?;
+
+        rows.map(|row| row.map_err(crate::Error::from)).collect()
+    }
+
+    pub fn checkouts(&self, status: Option<&str>) -> crate::Result<Vec<crate::Checkout>> {
+        let mut statement = self.connection.prepare(
+            "select data from checkouts where ?1 is null or status = ?1 order by date, id",
+        )?;
+
+        let rows = statement.query_map([status], |row| row.get::<_, String>(0))?;
+
+        rows.map(|data| Ok(serde_json::from_str(&data?)?)).collect()
+    }
+}
+
+impl crate::sync::Sink for Store {
+    fn insert(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        self.upsert_transaction(transaction)
+    }
+
+    fn update(&mut self, transaction: &crate::Transaction) -> crate::Result {
+        self.upsert_transaction(transaction)
+    }
+}
+
+fn upsert_transaction(
+    connection: &rusqlite::Connection,
+    transaction: &crate::Transaction,
+) -> crate::Result {
+    connection.execute(
+        "insert into transactions (
+            id, transaction_code, amount, currency, timestamp, status, payment_type,
+            merchant_code, username, vat_amount, tip_amount, data
+        ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+        on conflict (id) do update set
+            transaction_code = excluded.transaction_code,
+            amount = excluded.amount,
+            currency = excluded.currency,
+            timestamp = excluded.timestamp,
+            status = excluded.status,
+            payment_type = excluded.payment_type,
+            merchant_code = excluded.merchant_code,
+            username = excluded.username,
+            vat_amount = excluded.vat_amount,
+            tip_amount = excluded.tip_amount,
+            data = excluded.data",
+        rusqlite::params![
+            transaction.id,
+            transaction.transaction_code,
+            transaction.amount,
+            transaction.currency,
+            transaction.timestamp,
+            transaction.status.to_string(),
+            transaction.payment_type.to_string(),
+            transaction.merchant_code,
+            transaction.username,
+            transaction.vat_amount,
+            transaction.tip_amount,
+            serde_json::to_string(transaction)?,
+        ],
+    )?;
+
+    for table in ["products", "events", "transaction_events"] {
+        connection.execute(
+            &format!("delete from {table} where transaction_id = ?1"),
+            [&transaction.id],
+        )?;
+    }
+
+    for (position, product) in transaction.products.iter().enumerate() {
+        connection.execute(
+            "insert into products (
+                transaction_id, position, name, price, vat_rate, single_vat_amount,
+                price_with_vat, vat_amount, quantity, total_price, total_with_vat
+            ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                transaction.id,
+                position,
+                product.name,
+                product.price,
+                product.vat_rate,
+                product.single_vat_amount,
+                product.price_with_vat,
+                product.vat_amount,
+                product.quantity,
+                product.total_price,
+                product.total_with_vat,
+            ],
+        )?;
+    }
+
+    for event in &transaction.events {
+        connection.execute(
+            "insert into events (
+                id, transaction_id, type, status, amount, timestamp, fee_amount,
+                installment_number, deducted_amount, deducted_fee_amount
+            ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                event.id,
+                transaction.id,
+                event.ty,
+                event.status,
+                event.amount,
+                event.timestamp,
+                event.fee_amount,
+                event.installment_number,
+                event.deducted_amount,
+                event.deducted_fee_amount,
+            ],
+        )?;
+    }
+
+    for event in &transaction.transaction_events {
+        connection.execute(
+            "insert into transaction_events (
+                id, transaction_id, event_type, status, amount, due_date, date,
+                installment_number, timestamp
+            ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                event.id,
+                transaction.id,
+                event.event_type,
+                event.status,
+                event.amount,
+                event.due_date,
+                event.date,
+                event.installment_number,
+                event.timestamp,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn upsert_payout(connection: &rusqlite::Connection, payout: &crate::Payout) -> crate::Result {
+    connection.execute(
+        "insert into payouts (
+            id, amount, currency, date, fee, reference, status, transaction_code, type
+        ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        on conflict (id) do update set
+            amount = excluded.amount,
+            currency = excluded.currency,
+            date = excluded.date,
+            fee = excluded.fee,
+            reference = excluded.reference,
+            status = excluded.status,
+            transaction_code = excluded.transaction_code,
+            type = excluded.type",
+        rusqlite::params![
+            payout.id,
+            payout.amount,
+            payout.currency,
+            payout.date,
+            payout.fee,
+            payout.reference,
+            payout.status,
+            payout.transaction_code,
+            payout.ty,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn upsert_checkout(connection: &rusqlite::Connection, checkout: &crate::Checkout) -> crate::Result {
+    let Some(id) = &checkout.id else {
+        return Ok(());
+    };
+
+    // `Checkout` skips the response only fields when serialized
+    let mut data = serde_json::to_value(checkout)?;
+    data["id"] = serde_json::json!(id);
+    data["status"] = serde_json::json!(checkout.status);
+    data["date"] = serde_json::json!(checkout.date);
+
+    connection.execute(
+        "insert into checkouts (
+            id, checkout_reference, amount, currency, merchant_code, status, date, data
+        ) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        on conflict (id) do update set
+            checkout_reference = excluded.checkout_reference,
+            amount = excluded.amount,
+            currency = excluded.currency,
+            merchant_code = excluded.merchant_code,
+            status = excluded.status,
+            date = excluded.date,
+            data = excluded.data",
+        rusqlite::params![
+            id,
+            checkout.checkout_reference,
+            checkout.amount,
+            checkout.currency,
+            checkout.merchant_code,
+            checkout.status,
+            checkout.date,
+            data.to_string(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn placeholders(n: usize) -> String {
+    vec!["?"; n].join(", ")
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("where {}", conditions.join(" and "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    fn transaction() -> crate::Transaction {
+        serde_json::from_str(include_str!("../fixtures/transaction.json")).unwrap()
+    }
+
+    #[test]
+    fn transactions() -> crate::Result {
+        let mut store = crate::sqlite::Store::in_memory()?;
+        let mut transaction = transaction();
+
+        store.upsert_transaction(&transaction)?;
+        transaction.status = crate::Status::Refunded;
+        store.upsert_transaction(&transaction)?;
+
+        let count: u32 =
+            store
+                .connection()
+                .query_row("select count(*) from products", [], |row| row.get(0))?;
+        assert_eq!(count, 2);
+
+        let query = crate::sqlite::Query {
+            start_date: Some("2021-01-05".to_string()),
+            end_date: Some("2021-01-05".to_string()),
+            statuses: vec![crate::Status::Refunded],
+            payment_types: vec![crate::PaymentType::Pos],
+            username: Some("merchant@example.org".to_string()),
+        };
+        let transactions = store.transactions(&query)?;
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].products.len(), 2);
+
+        let query = crate::sqlite::Query {
+            statuses: vec![crate::Status::Successful],
+
+            ..Default::default()
+        };
+        assert!(store.transactions(&query)?.is_empty());
+
+        assert!(store.transaction(&transaction.id)?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn payouts() -> crate::Result {
+        let mut store = crate::sqlite::Store::in_memory()?;
+
+        let mut payout = crate::Payout {
+            amount: 9.57,
+            currency: "EUR".to_string(),
+            date: "2021-01-07".to_string(),
+            fee: 0.19,
+            id: 1,
+            reference: "REF".to_string(),
+            status: "SUCCESSFUL".to_string(),
+            transaction_code: "TEENSK4W2K".to_string(),
+            ty: "PAYOUT".to_string(),
+        };
+
+        store.upsert_payout(&payout)?;
+        payout.fee = 0.2;
+        store.upsert_payout(&payout)?;
+
+        let payouts = store.payouts("2021-01-01", "2021-01-31", None)?;
+        assert_eq!(payouts.len(), 1);
+        assert_eq!(payouts[0].fee, 0.2);
+        assert!(
+            store
+                .payouts("2021-01-01", "2021-01-31", Some("FAILED"))?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn checkouts() -> crate::Result {
+        let mut store = crate::sqlite::Store::in_memory()?;
+
+        let checkout = crate::Checkout {
+            id: Some("4e425463-3e1b-431d-83fa-1e51c2925e99".to_string()),
+            amount: 2.2,
+            currency: "EUR".to_string(),
+            checkout_reference: "1".to_string(),
+            status: Some("PENDING".to_string()),
+
+            ..Default::default()
+        };
+
+        store.upsert_checkout(&checkout)?;
+
+        let checkouts = store.checkouts(Some("PENDING"))?;
+        assert_eq!(checkouts.len(), 1);
+        assert_eq!(checkouts[0].id, checkout.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn populate() -> crate::Result {
+        let api = crate::test::api()?;
+        let mut store = crate::sqlite::Store::in_memory()?;
+
+        let filter = crate::services::transactions::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2021-12-31".to_string(),
+
+            ..Default::default()
+        };
+
+        let count = store.populate_transactions(&api.transactions(), &filter)?;
+        assert_eq!(
+            store.transactions(&crate::sqlite::Query::default())?.len(),
+            count
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn populate_pages() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+        let mut store = crate::sqlite::Store::in_memory()?;
+
+        let checkout = crate::Checkout {
+            amount: 2.2,
+            currency: "EUR".to_string(),
+            checkout_reference: "populate".to_string(),
+            merchant_code: crate::mock::MERCHANT_CODE.to_string(),
+
+            ..Default::default()
+        };
+        api.checkout().create(&checkout)?;
+        let id = api.checkout().find_by_reference_id("populate")?.id;
+        api.checkout().pay(
+            id.as_deref().unwrap_or_default(),
+            crate::mock::CUSTOMER_ID,
+            crate::mock::CARD_TOKEN,
+            None,
+        )?;
+
+        let filter = crate::services::transactions::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2999-12-31".to_string(),
+            limit: Some(1),
+
+            ..Default::default()
+        };
+
+        let count = store.populate_transactions(&api.transactions(), &filter)?;
+        assert_eq!(count, 2);
+        assert_eq!(
+            store.transactions(&crate::sqlite::Query::default())?.len(),
+            2
+        );
+
+        server.push_payout(crate::Payout {
+            id: 2,
+            date: "2021-01-08".to_string(),
+
+            ..Default::default()
+        });
+
+        let filter = crate::services::payouts::Filter {
+            start_date: "2021-01-01".to_string(),
+            end_date: "2021-01-31".to_string(),
+            limit: Some(1),
+            descending_order: false,
+        };
+
+        let count = store.populate_payouts(&api.payouts(), &filter)?;
+        assert_eq!(count, 2);
+        assert_eq!(store.payouts("2021-01-01", "2021-01-31", None)?.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,503 @@
+const NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.02";
+
+/**
+ * Writes the statement as ISO 20022 `camt.053.001.02` XML document.
+ */
+pub fn write(statement: &super::Statement, w: &mut impl std::io::Write) -> crate::Result {
+    let currency = escape(&statement.account.currency);
+    let created_at = statement.created_at.trim_end_matches('Z');
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<Document xmlns="{NAMESPACE}">"#)?;
+    writeln!(w, "  <BkToCstmrStmt>")?;
+    writeln!(w, "    <GrpHdr>")?;
+    writeln!(w, "      <MsgId>{}</MsgId>", required(&statement.id, 35))?;
+    writeln!(w, "      <CreDtTm>{created_at}</CreDtTm>")?;
+    writeln!(w, "    </GrpHdr>")?;
+    writeln!(w, "    <Stmt>")?;
+    writeln!(w, "      <Id>{}</Id>", required(&statement.id, 35))?;
+    writeln!(w, "      <CreDtTm>{created_at}</CreDtTm>")?;
+    writeln!(w, "      <FrToDt>")?;
+    writeln!(
+        w,
+        "        <FrDtTm>{}T00:00:00</FrDtTm>",
+        statement.from_date
+    )?;
+    writeln!(w, "        <ToDtTm>{}T23:59:59</ToDtTm>", statement.to_date)?;
+    writeln!(w, "      </FrToDt>")?;
+    writeln!(w, "      <Acct>")?;
+    writeln!(w, "        <Id>")?;
+    if is_iban(&statement.account.id) {
+        writeln!(
+            w,
+            "          <IBAN>{}</IBAN>",
+            escape(&statement.account.id)
+        )?;
+    } else {
+        writeln!(w, "          <Othr>")?;
+        writeln!(
+            w,
+            "            <Id>{}</Id>",
+            required(&statement.account.id, 34)
+        )?;
+        writeln!(w, "          </Othr>")?;
+    }
+    writeln!(w, "        </Id>")?;
+    writeln!(w, "        <Ccy>{currency}</Ccy>")?;
+    if let Some(owner) = statement.account.owner.as_deref().filter(|x| !x.is_empty()) {
+        writeln!(w, "        <Ownr>")?;
+        writeln!(w, "          <Nm>{}</Nm>", text(owner, 140))?;
+        writeln!(w, "        </Ownr>")?;
+    }
+    writeln!(w, "      </Acct>")?;
+    balance(
+        w,
+        "OPBD",
+        statement.opening_balance,
+        &currency,
+        &statement.from_date,
+    )?;
+    balance(
+        w,
+        "CLBD",
+        statement.closing_balance(),
+        &currency,
+        &statement.to_date,
+    )?;
+
+    for entry in &statement.entries {
+        writeln!(w, "      <Ntry>")?;
+        if !entry.reference.is_empty() {
+            writeln!(
+                w,
+                "        <NtryRef>{}</NtryRef>",
+                text(&entry.reference, 35)
+            )?;
+        }
+        write_amount(w, "        ", entry.amount, &currency)?;
+        writeln!(w, "        <Sts>BOOK</Sts>")?;
+        writeln!(w, "        <BookgDt>")?;
+        writeln!(w, "          <Dt>{}</Dt>", entry.booking_date)?;
+        writeln!(w, "        </BookgDt>")?;
+        writeln!(w, "        <ValDt>")?;
+        writeln!(w, "          <Dt>{}</Dt>", entry.value_date)?;
+        writeln!(w, "        </ValDt>")?;
+        writeln!(w, "        <BkTxCd>")?;
+        writeln!(w, "          <Prtry>")?;
+        writeln!(w, "            <Cd>PAYOUT</Cd>")?;
+        writeln!(w, "            <Issr>SumUp</Issr>")?;
+        writeln!(w, "          </Prtry>")?;
+        writeln!(w, "        </BkTxCd>")?;
+        if entry.fee != 0. {
+            writeln!(w, "        <Chrgs>")?;
+            writeln!(
+                w,
+                r#"          <Amt Ccy="{currency}">{:.2}</Amt>"#,
+                entry.fee.abs()
+            )?;
+            writeln!(w, "          <CdtDbtInd>DBIT</CdtDbtInd>")?;
+            writeln!(w, "        </Chrgs>")?;
+        }
+        writeln!(w, "        <NtryDtls>")?;
+        for detail in &entry.details {
+            writeln!(w, "          <TxDtls>")?;
+            if !detail.reference.is_empty() {
+                writeln!(w, "            <Refs>")?;
+                writeln!(
+                    w,
+                    "              <EndToEndId>{}</EndToEndId>",
+                    text(&detail.reference, 35)
+                )?;
+                writeln!(w, "            </Refs>")?;
+            }
+            writeln!(w, "            <AmtDtls>")?;
+            writeln!(w, "              <TxAmt>")?;
+            writeln!(
+                w,
+                r#"                <Amt Ccy="{currency}">{:.2}</Amt>"#,
+                detail.amount.abs()
+            )?;
+            writeln!(w, "              </TxAmt>")?;
+            writeln!(w, "            </AmtDtls>")?;
+            writeln!(w, "          </TxDtls>")?;
+        }
+        writeln!(w, "        </NtryDtls>")?;
+        if !entry.description.is_empty() {
+            writeln!(
+                w,
+                "        <AddtlNtryInf>{}</AddtlNtryInf>",
+                text(&entry.description, 500)
+            )?;
+        }
+        writeln!(w, "      </Ntry>")?;
+    }
+
+    writeln!(w, "    </Stmt>")?;
+    writeln!(w, "  </BkToCstmrStmt>")?;
+    writeln!(w, "</Document>")?;
+
+    Ok(())
+}
+
+/**
+ * Parses the statements of a `camt.053` document, whatever its version.
+ *
+ * Only the fields needed to match payouts are read: account, balances, booking and value dates,
+ * amounts, references and remittance information.
+ */
+pub fn parse(xml: &str) -> crate::Result<Vec<super::Statement>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut path = Vec::<String>::new();
+    let mut statements = Vec::new();
+    let mut statement = super::Statement::default();
+    let mut entry = super::Entry::default();
+    let mut detail = super::Detail::default();
+    let mut balance = Balance::default();
+
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+
+                match name.as_str() {
+                    "Stmt" => statement = super::Statement::default(),
+                    "Ntry" => entry = super::Entry::default(),
+                    "TxDtls" => detail = super::Detail::default(),
+                    "Bal" => balance = Balance::default(),
+                    _ => (),
+                }
+
+                path.push(name);
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    return Err(invalid("unexpected closing tag"));
+                };
+
+                match name.as_str() {
+                    "Stmt" => statements.push(std::mem::take(&mut statement)),
+                    "Ntry" => {
+                        if entry.booking_date.is_empty() {
+                            entry.booking_date.clone_from(&entry.value_date);
+                        }
+                        if entry.value_date.is_empty() {
+                            entry.value_date.clone_from(&entry.booking_date);
+                        }
+                        statement.entries.push(std::mem::take(&mut entry));
+                    }
+                    "TxDtls" => entry.details.push(std::mem::take(&mut detail)),
+                    "Bal" if matches!(balance.code.as_str(), "OPBD" | "PRCD") => {
+                        statement.opening_balance = balance.amount;
+                    }
+                    _ => (),
+                }
+            }
+            Event::Text(e) => {
+                let text = e.unescape().map_err(invalid)?.to_string();
+                let path = path.join("/");
+
+                text_event(
+                    &path,
+                    text,
+                    &mut statement,
+                    &mut entry,
+                    &mut detail,
+                    &mut balance,
+                )?;
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(statements)
+}
+
+#[derive(Default)]
+struct Balance {
+    code: String,
+    amount: f32,
+}
+
+fn text_event(
+    path: &str,
+    text: String,
+    statement: &mut super::Statement,
+    entry: &mut super::Entry,
+    detail: &mut super::Detail,
+    balance: &mut Balance,
+) -> crate::Result {
+    if path.contains("/Ntry/") {
+        if path.ends_with("/TxDtls/Refs/EndToEndId") {
+            detail.reference = text;
+        } else if path.ends_with("/TxDtls/AmtDtls/TxAmt/Amt") {
+            detail.amount = amount(&text)?;
+        } else if path.ends_with("/RmtInf/Ustrd") || path.ends_with("/Ntry/AddtlNtryInf") {
+            if !entry.description.is_empty() {
+                entry.description.push(' ');
+            }
+            entry.description.push_str(&text);
+        } else if path.ends_with("/Ntry/NtryRef") || path.ends_with("/Ntry/AcctSvcrRef") {
+            if entry.reference.is_empty() {
+                entry.reference = text;
+            }
+        } else if path.ends_with("/Ntry/Amt") {
+            entry.amount = amount(&text)?;
+        } else if path.ends_with("/Ntry/CdtDbtInd") {
+            if text == "DBIT" {
+                entry.amount = -entry.amount.abs();
+            }
+        } else if path.ends_with("/Ntry/Chrgs/Amt") || path.ends_with("/Ntry/Chrgs/Rcrd/Amt") {
+            entry.fee += amount(&text)?;
+        } else if path.contains("/Ntry/BookgDt/") {
+            entry.booking_date = date(&text);
+        } else if path.contains("/Ntry/ValDt/") {
+            entry.value_date = date(&text);
+        }
+    } else if path.contains("/Bal/") {
+        if path.ends_with("/Tp/CdOrPrtry/Cd") {
+            balance.code = text;
+        } else if path.ends_with("/Bal/Amt") {
+            balance.amount = amount(&text)?;
+        } else if path.ends_with("/Bal/CdtDbtInd") && text == "DBIT" {
+            balance.amount = -balance.amount.abs();
+        }
+    } else if path.ends_with("/Stmt/Id") {
+        statement.id = text;
+    } else if path.ends_with("/Stmt/CreDtTm") {
+        statement.created_at = text;
+    } else if path.ends_with("/FrToDt/FrDtTm") {
+        statement.from_date = date(&text);
+    } else if path.ends_with("/FrToDt/ToDtTm") {
+        statement.to_date = date(&text);
+    } else if path.ends_with("/Acct/Id/IBAN") || path.ends_with("/Acct/Id/Othr/Id") {
+        statement.account.id = text;
+    } else if path.ends_with("/Acct/Ccy") {
+        statement.account.currency = text;
+    } else if path.ends_with("/Acct/Ownr/Nm") {
+        statement.account.owner = Some(text);
+    }
+
+    Ok(())
+}
+
+fn amount(s: &str) -> crate::Result<f32> {
+    s.parse()
+        .map_err(|_| crate::Error::InvalidStatement(format!("invalid amount {s}")))
+}
+
+fn date(s: &str) -> String {
+    s.get(..10).unwrap_or(s).to_string()
+}
+
+fn invalid(err: impl std::fmt::Display) -> crate::Error {
+    crate::Error::InvalidStatement(err.to_string())
+}
+
+fn balance(
+    w: &mut impl std::io::Write,
+    code: &str,
+    value: f32,
+    currency: &str,
+    date: &str,
+) -> crate::Result {
+    writeln!(w, "      <Bal>")?;
+    writeln!(w, "        <Tp>")?;
+    writeln!(w, "          <CdOrPrtry>")?;
+    writeln!(w, "            <Cd>{code}</Cd>")?;
+    writeln!(w, "          </CdOrPrtry>")?;
+    writeln!(w, "        </Tp>")?;
+    write_amount(w, "        ", value, currency)?;
+    writeln!(w, "        <Dt>")?;
+    writeln!(w, "          <Dt>{date}</Dt>")?;
+    writeln!(w, "        </Dt>")?;
+    writeln!(w, "      </Bal>")?;
+
+    Ok(())
+}
+
+fn write_amount(
+    w: &mut impl std::io::Write,
+    indent: &str,
+    value: f32,
+    currency: &str,
+) -> crate::Result {
+    let indicator = if value < 0. { "DBIT" } else { "CRDT" };
+
+    writeln!(
+        w,
+        r#"{indent}<Amt Ccy="{currency}">{:.2}</Amt>"#,
+        value.abs()
+    )?;
+    writeln!(w, "{indent}<CdtDbtInd>{indicator}</CdtDbtInd>")?;
+
+    Ok(())
+}
+
+fn is_iban(id: &str) -> bool {
+    id.len() > 4
+        && id.len() <= 34
+        && id[..2].chars().all(|c| c.is_ascii_uppercase())
+        && id[2..4].chars().all(|c| c.is_ascii_digit())
+        && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/**
+ * `MaxNText` value, truncated to `max` characters.
+ */
+fn text(s: &str, max: usize) -> String {
+    escape(&s.chars().take(max).collect::<String>())
+}
+
+/**
+ * Same as [`text`] for mandatory elements, which can't be empty.
+ */
+fn required(s: &str, max: usize) -> String {
+    if s.is_empty() {
+        "NOTPROVIDED".to_string()
+    } else {
+        text(s, max)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn write() -> crate::Result {
+        let statement = crate::statement::test::statement(crate::statement::Granularity::Payout);
+
+        let mut xml = Vec::new();
+        crate::statement::camt053::write(&statement, &mut xml)?;
+
+        assert_eq!(
+            String::from_utf8(xml).unwrap(),
+            include_str!("../../fixtures/statement/camt053.xml")
+        );
+
+        Ok(())
+    }
+
+    /**
+     * Validates with `xmllint` against the official schema, `None` when either is missing.
+     */
+    fn xmllint(xml: &str) -> Option<bool> {
+        use std::io::Write as _;
+
+        let schema = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/statement/camt.053.001.02.xsd"
+        );
+        if !std::path::Path::new(schema).exists() {
+            log::warn!("{schema} is missing, download it from iso20022.org");
+            return None;
+        }
+
+        let mut child = match std::process::Command::new("xmllint")
+            .args(["--noout", "--schema", schema, "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => {
+                log::warn!("xmllint is not available: {err}");
+                return None;
+            }
+        };
+
+        child.stdin.take()?.write_all(xml.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        if !output.status.success() {
+            log::info!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        Some(output.status.success())
+    }
+
+    #[test]
+    fn schema() -> crate::Result {
+        let mut edge = crate::statement::test::statement(crate::statement::Granularity::Payout);
+        edge.id = "S".repeat(40);
+        edge.account.id = "ACCOUNT-".repeat(5);
+        edge.account.owner = Some(String::new());
+        edge.entries[0].reference = String::new();
+        edge.entries[0].description = "é".repeat(600);
+        edge.entries[0].details[0].reference = "R".repeat(40);
+        edge.entries[0].details[1].reference = String::new();
+
+        for statement in [
+            crate::statement::test::statement(crate::statement::Granularity::Payout),
+            crate::statement::test::statement(crate::statement::Granularity::Transaction),
+            edge,
+        ] {
+            let mut xml = Vec::new();
+            crate::statement::camt053::write(&statement, &mut xml)?;
+
+            let Some(valid) = xmllint(&String::from_utf8(xml).unwrap()) else {
+                return Ok(());
+            };
+            assert!(valid);
+        }
+
+        let xml = include_str!("../../fixtures/statement/camt053.xml");
+        assert_eq!(xmllint(&xml.replace("<Sts>BOOK</Sts>", "")), Some(false));
+        assert_eq!(xmllint(&xml.replace("CRDT", "CREDIT")), Some(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> crate::Result {
+        let statements =
+            crate::statement::camt053::parse(include_str!("../../fixtures/statement/camt053.xml"))?;
+
+        let expected = crate::statement::test::statement(crate::statement::Granularity::Payout);
+
+        assert_eq!(statements.len(), 1);
+
+        let statement = &statements[0];
+        assert_eq!(statement.id, expected.id);
+        assert_eq!(statement.account, expected.account);
+        assert_eq!(statement.from_date, expected.from_date);
+        assert_eq!(statement.to_date, expected.to_date);
+        assert_eq!(statement.entries.len(), 1);
+        assert_eq!(statement.entries[0].reference, "PO-0107");
+        assert_eq!(statement.entries[0].details.len(), 2);
+        assert!((statement.closing_balance() - expected.closing_balance()).abs() < 0.001);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_bank() -> crate::Result {
+        let statements = crate::statement::camt053::parse(include_str!(
+            "../../fixtures/statement/camt053-bank.xml"
+        ))?;
+
+        assert_eq!(statements.len(), 1);
+
+        let statement = &statements[0];
+        assert_eq!(statement.account.id, "DE89370400440532013000");
+        assert_eq!(statement.opening_balance, 1000.);
+        assert_eq!(statement.entries.len(), 2);
+        assert_eq!(statement.entries[0].amount, 29.18);
+        assert_eq!(statement.entries[0].booking_date, "2021-01-08");
+        assert_eq!(statement.entries[0].description, "SUMUP PAYOUT PO-0107");
+        assert_eq!(statement.entries[1].amount, -45.);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+/**
+ * How close a bank credit must be from a payout to be paired with it.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /** Maximum number of days between the payout date and the credit booking date */
+    pub window_days: i64,
+    /** Maximum difference between the payout net amount and the credit */
+    pub tolerance: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            window_days: 3,
+            tolerance: 0.01,
+        }
+    }
+}
+
+/**
+ * One bank transfer expected from SumUp, all the payout lines sharing the same `reference`.
+ */
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct Transfer {
+    pub reference: String,
+    pub date: String,
+    pub currency: String,
+    /** Sum of the payout lines, fees deducted */
+    pub amount: f32,
+    pub payouts: Vec<crate::Payout>,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Match {
+    pub transfer: Transfer,
+    pub credit: super::Entry,
+    /** `true` if the credit remittance information contains the payout reference */
+    pub by_reference: bool,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct Report {
+    pub matched: Vec<Match>,
+    /** Transfers without any bank credit */
+    pub missing_payouts: Vec<Transfer>,
+    /** Bank credits without any transfer */
+    pub unmatched_credits: Vec<super::Entry>,
+}
+
+impl Report {
+    #[must_use]
+    pub fn is_reconciled(&self) -> bool {
+        self.missing_payouts.is_empty() && self.unmatched_credits.is_empty()
+    }
+}
+
+/**
+ * Groups the payout lines by `reference`, in the order they appear.
+ */
+#[must_use]
+pub fn transfers(payouts: &[crate::Payout]) -> Vec<Transfer> {
+    let mut transfers = Vec::<Transfer>::new();
+
+    for payout in payouts {
+        let index = transfers
+            .iter()
+            .position(|x| x.reference == payout.reference)
+            .unwrap_or_else(|| {
+                transfers.push(Transfer {
+                    reference: payout.reference.clone(),
+                    date: payout.date.clone(),
+                    currency: payout.currency.clone(),
+
+                    ..Default::default()
+                });
+                transfers.len() - 1
+            });
+        let transfer = &mut transfers[index];
+
+        transfer.amount += payout.amount - payout.fee;
+        transfer.payouts.push(payout.clone());
+    }
+
+    transfers
+}
+
+/**
+ * Pairs the credits of bank statements with the payouts.
+ *
+ * A credit matches a transfer if their amounts are within `tolerance` and the credit is booked
+ * at most `window_days` around the payout date. When several credits match, the one mentioning
+ * the payout reference is preferred, then the closest in time. Debits are ignored.
+ */
+#[must_use]
+pub fn pair(
+    statements: &[super::Statement],
+    payouts: &[crate::Payout],
+    options: &Options,
+) -> Report {
+    let mut credits = statements
+        .iter()
+        .flat_map(|x| &x.entries)
+        .filter(|x| x.amount > 0.)
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    let mut report = Report::default();
+
+    for transfer in transfers(payouts) {
+        let candidate = credits
+            .iter()
+            .enumerate()
+            .filter_map(|(n, x)| x.map(|x| (n, x)))
+            .filter(|(_, x)| (x.amount - transfer.amount).abs() <= options.tolerance)
+            .filter_map(|(n, x)| {
+                let distance = distance(&transfer.date, &x.booking_date)?;
+
+                (distance <= options.window_days).then(|| {
+                    let by_reference = mentions(x, &transfer.reference);
+
+                    (n, by_reference, distance)
+                })
+            })
+            .min_by_key(|(_, by_reference, distance)| (!by_reference, *distance));
+
+        match candidate {
+            Some((n, by_reference, _)) => {
+                let credit = credits[n].take().cloned().unwrap_or_default();
+
+                report.matched.push(Match {
+                    transfer,
+                    credit,
+                    by_reference,
+                });
+            }
+            None => report.missing_payouts.push(transfer),
+        }
+    }
+
+    report.unmatched_credits = credits.into_iter().flatten().cloned().collect();
+
+    report
+}
+
+/**
+ * Fetches the payouts of the statements period and pairs them with the bank credits.
+ *
+ * The period starts `window_days` before the first day of the statements, or their first booking
+ * date, so the payouts credited by the bank on the first days are found. A payout is only reported missing when its
+ * whole window fits in the statements period, the others may be credited in the previous or the
+ * next statement.
+ */
+pub fn fetch(
+    payouts: &crate::services::Payouts,
+    statements: &[super::Statement],
+    options: &Options,
+) -> crate::Result<Report> {
+    let dates = statements
+        .iter()
+        .flat_map(|x| &x.entries)
+        .filter_map(|x| crate::date::Date::parse(&x.booking_date))
+        .map(crate::date::Date::days)
+        .collect::<Vec<_>>();
+
+    let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) else {
+        return Ok(Report::default());
+    };
+
+    let from = statements
+        .iter()
+        .filter_map(|x| day(&x.from_date))
+        .min()
+        .unwrap_or(*first);
+    let to = statements
+        .iter()
+        .filter_map(|x| day(&x.to_date))
+        .max()
+        .unwrap_or(*last);
+
+    let filter = crate::services::payouts::Filter {
+        start_date: crate::date::Date::from_days(from.min(*first) - options.window_days)
+            .to_string(),
+        end_date: crate::date::Date::from_days(to.max(*last)).to_string(),
+        limit: Some(100),
+        descending_order: false,
+    };
+
+    let payouts = payouts
+        .pages(&filter)
+        .collect::<crate::Result<Vec<_>>>()?
+        .concat();
+
+    let mut report = pair(statements, &payouts, options);
+    report.missing_payouts.retain(|x| {
+        day(&x.date).is_some_and(|date| {
+            date - options.window_days >= from && date + options.window_days <= to
+        })
+    });
+
+    Ok(report)
+}
+
+fn day(date: &str) -> Option<i64> {
+    crate::date::Date::parse(date).map(crate::date::Date::days)
+}
+
+fn mentions(entry: &super::Entry, reference: &str) -> bool {
+    let reference = reference.to_uppercase();
+
+    !reference.is_empty()
+        && (entry.reference.to_uppercase().contains(&reference)
+            || entry.description.to_uppercase().contains(&reference))
+}
+
+fn distance(a: &str, b: &str) -> Option<i64> {
+    let a = crate::date::Date::parse(a)?;
+    let b = crate::date::Date::parse(b)?;
+
+    Some((a.days() - b.days()).abs())
+}
+
+#[cfg(test)]
+mod test {
+    fn bank() -> crate::statement::Statement {
+        crate::statement::camt053::parse(include_str!("../../fixtures/statement/camt053-bank.xml"))
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn pair() {
+        let payouts = crate::statement::test::payouts();
+
+        let report = crate::statement::matching::pair(
+            &[bank()],
+            &payouts,
+            &crate::statement::matching::Options::default(),
+        );
+
+        assert_eq!(report.matched.len(), 1);
+        assert_eq!(report.matched[0].transfer.reference, "PO-0107");
+        assert_eq!(report.matched[0].transfer.payouts.len(), 2);
+        assert!(report.matched[0].by_reference);
+        assert_eq!(report.missing_payouts.len(), 1);
+        assert_eq!(report.missing_payouts[0].reference, "PO-0201");
+        assert!(report.unmatched_credits.is_empty());
+        assert!(!report.is_reconciled());
+    }
+
+    #[test]
+    fn pair_prefers_reference() {
+        let mut statement = bank();
+        let mut other = statement.entries[0].clone();
+        other.description = "Refund".to_string();
+        other.booking_date = "2021-01-07".to_string();
+        statement.entries.insert(0, other);
+
+        let report = crate::statement::matching::pair(
+            &[statement],
+            &crate::statement::test::payouts()[..2],
+            &crate::statement::matching::Options::default(),
+        );
+
+        assert_eq!(report.matched.len(), 1);
+        assert_eq!(report.matched[0].credit.booking_date, "2021-01-08");
+        assert_eq!(report.unmatched_credits.len(), 1);
+        assert_eq!(report.unmatched_credits[0].description, "Refund");
+    }
+
+    #[test]
+    fn pair_window() {
+        let mut statement = bank();
+        statement.entries[0].booking_date = "2021-01-15".to_string();
+
+        let report = crate::statement::matching::pair(
+            &[statement],
+            &crate::statement::test::payouts()[..2],
+            &crate::statement::matching::Options::default(),
+        );
+
+        assert!(report.matched.is_empty());
+        assert_eq!(report.missing_payouts.len(), 1);
+        assert_eq!(report.unmatched_credits.len(), 1);
+    }
+
+    #[test]
+    fn fetch() -> crate::Result {
+        let api = crate::test::api()?;
+
+        crate::statement::matching::fetch(
+            &api.payouts(),
+            &[bank()],
+            &crate::statement::matching::Options::default(),
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_period() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        server.push_payout(crate::Payout {
+            amount: 5.,
+            currency: "EUR".to_string(),
+            date: "2021-01-20".to_string(),
+            id: 2,
+            reference: "PO-0120".to_string(),
+
+            ..Default::default()
+        });
+
+        let statement = crate::statement::Statement {
+            from_date: "2021-01-09".to_string(),
+            to_date: "2021-01-31".to_string(),
+            entries: vec![crate::statement::Entry {
+                booking_date: "2021-01-25".to_string(),
+                amount: 1.,
+
+                ..Default::default()
+            }],
+
+            ..Default::default()
+        };
+
+        let report = crate::statement::matching::fetch(
+            &api.payouts(),
+            &[statement],
+            &crate::statement::matching::Options::default(),
+        )?;
+
+        // The seeded payout of 2021-01-07 may be in the previous statement
+        let missing = report
+            .missing_payouts
+            .iter()
+            .map(|x| x.reference.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, ["PO-0120"]);
+        assert_eq!(report.unmatched_credits.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+pub mod camt053;
+pub mod matching;
+pub mod mt940;
+
+/**
+ * The account the statement is about, SumUp payouts are credited to it.
+ */
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Account {
+    /** IBAN or any other account identifier */
+    pub id: String,
+    pub currency: String,
+    pub owner: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Granularity {
+    /** One entry per bank payout, grouping the payout lines by `reference` */
+    #[default]
+    Payout,
+    /** One entry per payout line, with its fee as sub-entry */
+    Transaction,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub id: String,
+    pub account: Account,
+    /** First day of the statement, inclusive `YYYY-MM-DD` */
+    pub from_date: String,
+    /** Last day of the statement, inclusive `YYYY-MM-DD` */
+    pub to_date: String,
+    pub opening_balance: f32,
+    pub granularity: Granularity,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Entry {
+    pub booking_date: String,
+    pub value_date: String,
+    /** Net amount, positive for credits and negative for debits */
+    pub amount: f32,
+    /** Charges already deducted from the amount */
+    pub fee: f32,
+    pub reference: String,
+    pub description: String,
+    pub details: Vec<Detail>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Detail {
+    pub reference: String,
+    pub amount: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Statement {
+    pub id: String,
+    pub created_at: String,
+    pub account: Account,
+    pub from_date: String,
+    pub to_date: String,
+    pub opening_balance: f32,
+    pub granularity: Granularity,
+    pub entries: Vec<Entry>,
+}
+
+impl Statement {
+    /**
+     * Builds a statement from the payouts lines dated between `from_date` and `to_date`.
+     */
+    #[must_use]
+    pub fn from_payouts(options: &Options, payouts: &[crate::Payout]) -> Self {
+        let payouts = payouts.iter().filter(|x| {
+            let day = x.date.get(..10).unwrap_or(&x.date);
+
+            day >= options.from_date.as_str() && day <= options.to_date.as_str()
+        });
+
+        let mut entries = Vec::<Entry>::new();
+
+        for payout in payouts {
+            let detail = Detail {
+                reference: payout.transaction_code.clone(),
+                amount: payout.amount,
+            };
+
+            match options.granularity {
+                Granularity::Payout => {
+                    if let Some(entry) =
+                        entries.iter_mut().find(|x| x.reference == payout.reference)
+                    {
+                        entry.amount += payout.amount - payout.fee;
+                        entry.fee += payout.fee;
+                        entry.details.push(detail);
+                    } else {
+                        entries.push(Entry {
+                            booking_date: payout.date.clone(),
+                            value_date: payout.date.clone(),
+                            amount: payout.amount - payout.fee,
+                            fee: payout.fee,
+                            reference: payout.reference.clone(),
+                            description: format!("SumUp payout {}", payout.reference),
+                            details: vec![detail],
+                        });
+                    }
+                }
+                Granularity::Transaction => entries.push(Entry {
+                    booking_date: payout.date.clone(),
+                    value_date: payout.date.clone(),
+                    amount: payout.amount - payout.fee,
+                    fee: payout.fee,
+                    reference: payout.reference.clone(),
+                    description: format!("SumUp {} {}", payout.ty, payout.reference),
+                    details: vec![detail],
+                }),
+            }
+        }
+
+        Self {
+            id: options.id.clone(),
+            created_at: crate::date::now_rfc3339(),
+            account: options.account.clone(),
+            from_date: options.from_date.clone(),
+            to_date: options.to_date.clone(),
+            opening_balance: options.opening_balance,
+            granularity: options.granularity,
+            entries,
+        }
+    }
+
+    /**
+     * Fetches the payouts of the statement period, page by page.
+     */
+    pub fn fetch(payouts: &crate::services::Payouts, options: &Options) -> crate::Result<Self> {
+        let filter = crate::services::payouts::Filter {
+            start_date: options.from_date.clone(),
+            end_date: options.to_date.clone(),
+            limit: Some(100),
+            descending_order: false,
+        };
+
+        let payouts = payouts
+            .pages(&filter)
+            .collect::<crate::Result<Vec<_>>>()?
+            .concat();
+
+        Ok(Self::from_payouts(options, &payouts))
+    }
+
+    #[must_use]
+    pub fn closing_balance(&self) -> f32 {
+        self.opening_balance + self.entries.iter().map(|x| x.amount).sum::<f32>()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    pub(crate) fn payouts() -> Vec<crate::Payout> {
+        let payout = |id, reference: &str, transaction_code: &str, amount, fee| crate::Payout {
+            amount,
+            currency: "EUR".to_string(),
+            date: "2021-01-07".to_string(),
+            fee,
+            id,
+            reference: reference.to_string(),
+            status: "SUCCESSFUL".to_string(),
+            transaction_code: transaction_code.to_string(),
+            ty: "PAYOUT".to_string(),
+        };
+
+        let mut payouts = vec![
+            payout(1, "PO-0107", "TEENSK4W2K", 9.76, 0.19),
+            payout(2, "PO-0107", "TEENSK4W3L", 20., 0.39),
+            payout(3, "PO-0201", "TEENSK4W4M", 5., 0.1),
+        ];
+        payouts[2].date = "2021-02-01".to_string();
+
+        payouts
+    }
+
+    pub(crate) fn statement(
+        granularity: crate::statement::Granularity,
+    ) -> crate::statement::Statement {
+        let options = crate::statement::Options {
+            id: "STMT-2021-01".to_string(),
+            account: crate::statement::Account {
+                id: "DE89370400440532013000".to_string(),
+                currency: "EUR".to_string(),
+                owner: Some("Café de Flore".to_string()),
+            },
+            from_date: "2021-01-01".to_string(),
+            to_date: "2021-01-31".to_string(),
+            opening_balance: 100.,
+            granularity,
+        };
+
+        let mut statement = crate::statement::Statement::from_payouts(&options, &payouts());
+        statement.created_at = "2021-02-01T08:00:00Z".to_string();
+
+        statement
+    }
+
+    #[test]
+    fn from_payouts() {
+        let by_payout = statement(crate::statement::Granularity::Payout);
+
+        assert_eq!(by_payout.entries.len(), 1);
+        assert_eq!(by_payout.entries[0].details.len(), 2);
+        assert!((by_payout.closing_balance() - 129.18).abs() < 0.001);
+
+        let by_transaction = statement(crate::statement::Granularity::Transaction);
+
+        assert_eq!(by_transaction.entries.len(), 2);
+        assert!((by_transaction.closing_balance() - 129.18).abs() < 0.001);
+    }
+}
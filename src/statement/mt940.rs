@@ -0,0 +1,544 @@
+/** Maximum length of a `:86:` line */
+const LINE_LENGTH: usize = 65;
+
+/**
+ * Writes the statement as SWIFT MT940 message, lines are separated by CRLF.
+ *
+ * With [`super::Granularity::Transaction`] every entry is written as a gross credit followed by
+ * its fee as a `NCHG` debit.
+ */
+pub fn write(statement: &super::Statement, w: &mut impl std::io::Write) -> crate::Result {
+    let currency = &statement.account.currency;
+
+    line(w, &format!(":20:{}", truncate(&statement.id, 16)))?;
+    line(w, &format!(":25:{}", truncate(&statement.account.id, 35)))?;
+    line(w, ":28C:00001/001")?;
+    line(
+        w,
+        &format!(
+            ":60F:{}",
+            balance(statement.opening_balance, &statement.from_date, currency)
+        ),
+    )?;
+
+    for entry in &statement.entries {
+        let mut information = entry.description.clone();
+        for detail in &entry.details {
+            information.push(' ');
+            information.push_str(&detail.reference);
+        }
+
+        if statement.granularity == super::Granularity::Transaction && entry.fee != 0. {
+            line(w, &transaction(entry, entry.amount + entry.fee, "NMSC"))?;
+            details(w, &information)?;
+            line(w, &transaction(entry, -entry.fee, "NCHG"))?;
+            details(w, &format!("Fee {information}"))?;
+        } else {
+            line(w, &transaction(entry, entry.amount, "NMSC"))?;
+            details(w, &information)?;
+        }
+    }
+
+    line(
+        w,
+        &format!(
+            ":62F:{}",
+            balance(statement.closing_balance(), &statement.to_date, currency)
+        ),
+    )?;
+    line(w, "-")?;
+
+    Ok(())
+}
+
+/**
+ * Parses the statements of a MT940 file, with or without SWIFT block headers.
+ *
+ * Fees booked as separate `:61:` lines stay separate entries, `:86:` structured subfields
+ * (`?20` to `?29`, `?32`, `?33` and `?60` to `?63`) are concatenated into the description.
+ */
+pub fn parse(mt940: &str) -> crate::Result<Vec<super::Statement>> {
+    let mut statements = Vec::new();
+    let mut statement: Option<super::Statement> = None;
+
+    for (tag, value) in fields(mt940) {
+        if tag == "20" {
+            if let Some(statement) = statement.take() {
+                statements.push(statement);
+            }
+            statement = Some(super::Statement {
+                id: value,
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(statement) = statement.as_mut() else {
+            return Err(invalid(format!("field :{tag}: before :20:")));
+        };
+
+        match tag.as_str() {
+            "25" => statement.account.id = value,
+            "60F" | "60M" => {
+                let (amount, date, currency) = parse_balance(&value)?;
+                statement.opening_balance = amount;
+                statement.from_date = date;
+                statement.account.currency = currency;
+            }
+            "62F" | "62M" => statement.to_date = parse_balance(&value)?.1,
+            "61" => statement.entries.push(parse_transaction(&value)?),
+            "86" => {
+                if let Some(entry) = statement.entries.last_mut() {
+                    entry.description = parse_details(&value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    statements.extend(statement);
+
+    Ok(statements)
+}
+
+/**
+ * Splits the message in `(tag, value)` fields, continuation lines are joined to their field.
+ */
+fn fields(mt940: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::<(String, String)>::new();
+
+    for line in mt940.lines() {
+        let mut line = line.trim_end_matches('\r');
+
+        if line.starts_with('{') {
+            match line.find("{4:") {
+                Some(start) => line = &line[start + 3..],
+                None => continue,
+            }
+        }
+
+        if line.is_empty() || line.starts_with('-') || line.starts_with('}') {
+            continue;
+        }
+
+        let tag = line
+            .strip_prefix(':')
+            .and_then(|x| x.split_once(':'))
+            .filter(|(tag, _)| tag.len() <= 3 && tag.starts_with(|c: char| c.is_ascii_digit()));
+
+        if let Some((tag, value)) = tag {
+            fields.push((tag.to_string(), value.to_string()));
+        } else if let Some((_, value)) = fields.last_mut() {
+            value.push('\n');
+            value.push_str(line);
+        }
+    }
+
+    fields
+}
+
+/**
+ * `:60F:` and `:62F:` balances: `C210101EUR100,00`.
+ */
+fn parse_balance(value: &str) -> crate::Result<(f32, String, String)> {
+    let sign = match value.get(..1) {
+        Some("C") => 1.,
+        Some("D") => -1.,
+        _ => return Err(invalid(format!("invalid balance {value}"))),
+    };
+    let date = value
+        .get(1..7)
+        .and_then(crate::date::Date::parse)
+        .ok_or_else(|| invalid(format!("invalid balance date {value}")))?;
+    let currency = value.get(7..10).unwrap_or_default().to_string();
+    let amount = parse_amount(value.get(10..).unwrap_or_default())?;
+
+    Ok((sign * amount, date.to_string(), currency))
+}
+
+/**
+ * `:61:` statement line: value date, optional entry date, mark, optional funds code, amount,
+ * transaction type and customer reference.
+ */
+fn parse_transaction(value: &str) -> crate::Result<super::Entry> {
+    let value = value.lines().next().unwrap_or_default();
+
+    let value_date = value
+        .get(..6)
+        .and_then(crate::date::Date::parse)
+        .ok_or_else(|| invalid(format!("invalid value date {value}")))?;
+    let mut rest = &value[6..];
+
+    let mut booking_date = value_date;
+    if rest.len() >= 4 && rest.as_bytes()[..4].iter().all(u8::is_ascii_digit) {
+        booking_date.month = rest[..2].parse().unwrap_or(value_date.month);
+        booking_date.day = rest[2..4].parse().unwrap_or(value_date.day);
+        if value_date.month == 1 && booking_date.month == 12 {
+            booking_date.year -= 1;
+        } else if value_date.month == 12 && booking_date.month == 1 {
+            booking_date.year += 1;
+        }
+        // Parsed again to reject a month or a day out of the calendar
+        booking_date = crate::date::Date::parse(&booking_date.to_string())
+            .ok_or_else(|| invalid(format!("invalid booking date {value}")))?;
+        rest = &rest[4..];
+    }
+
+    let (sign, mark) = if rest.starts_with("RC") {
+        (-1., 2)
+    } else if rest.starts_with("RD") {
+        (1., 2)
+    } else if rest.starts_with('C') {
+        (1., 1)
+    } else if rest.starts_with('D') {
+        (-1., 1)
+    } else {
+        return Err(invalid(format!("invalid debit/credit mark {value}")));
+    };
+    rest = &rest[mark..];
+
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..];
+    }
+
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(rest.len());
+    let amount = parse_amount(&rest[..end])?;
+    let rest = rest.get(end + 4..).unwrap_or_default();
+    let reference = rest.split("//").next().unwrap_or_default();
+
+    Ok(super::Entry {
+        booking_date: booking_date.to_string(),
+        value_date: value_date.to_string(),
+        amount: sign * amount,
+        reference: reference.to_string(),
+
+        ..Default::default()
+    })
+}
+
+fn parse_details(value: &str) -> String {
+    let value = value.replace('\n', "");
+
+    if !value.contains('?') {
+        return value;
+    }
+
+    value
+        .split('?')
+        .skip(1)
+        .filter_map(|x| {
+            let code = x.get(..2)?.parse::<u8>().ok()?;
+
+            matches!(code, 20..=29 | 32 | 33 | 60..=63).then(|| x[2..].trim())
+        })
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_amount(s: &str) -> crate::Result<f32> {
+    s.replace(',', ".")
+        .parse()
+        .map_err(|_| invalid(format!("invalid amount {s}")))
+}
+
+fn invalid(message: String) -> crate::Error {
+    crate::Error::InvalidStatement(message)
+}
+
+fn transaction(entry: &super::Entry, amount: f32, code: &str) -> String {
+    format!(
+        ":61:{}{}{}{}{code}{}//SUMUP",
+        yymmdd(&entry.value_date),
+        entry
+            .booking_date
+            .get(5..10)
+            .unwrap_or_default()
+            .replace('-', ""),
+        if amount < 0. { 'D' } else { 'C' },
+        format_amount(amount),
+        // `/` would be read as the start of the bank reference
+        truncate(&entry.reference.replace('/', "-"), 16),
+    )
+}
+
+/**
+ * `:86:` field, up to 6 lines of 65 characters.
+ */
+fn details(w: &mut impl std::io::Write, information: &str) -> crate::Result {
+    let chars = information
+        .chars()
+        .filter(char::is_ascii)
+        .map(swift)
+        .collect::<Vec<_>>();
+
+    for (n, chunk) in chars.chunks(LINE_LENGTH - 4).take(6).enumerate() {
+        // A continuation line starting with `:` or `-` would be read as a new field or the end
+        let prefix = match chunk.first() {
+            _ if n == 0 => ":86:",
+            Some(':' | '-') => " ",
+            _ => "",
+        };
+        line(w, &format!("{prefix}{}", chunk.iter().collect::<String>()))?;
+    }
+
+    Ok(())
+}
+
+fn balance(amount: f32, date: &str, currency: &str) -> String {
+    format!(
+        "{}{}{currency}{}",
+        if amount < 0. { 'D' } else { 'C' },
+        yymmdd(date),
+        format_amount(amount)
+    )
+}
+
+fn format_amount(amount: f32) -> String {
+    format!("{:.2}", amount.abs()).replace('.', ",")
+}
+
+fn yymmdd(date: &str) -> String {
+    date.get(2..10).unwrap_or_default().replace('-', "")
+}
+
+/**
+ * Mandatory field in the SWIFT character set, `NONREF` when empty.
+ */
+fn truncate(s: &str, max: usize) -> String {
+    let s = s
+        .chars()
+        .filter(char::is_ascii)
+        .map(swift)
+        .take(max)
+        .collect::<String>();
+
+    if s.is_empty() {
+        "NONREF".to_string()
+    } else {
+        s
+    }
+}
+
+/**
+ * Replaces the characters out of the SWIFT `x` character set by a space.
+ */
+fn swift(c: char) -> char {
+    if c.is_ascii_alphanumeric() || "/-?:().,'+ ".contains(c) {
+        c
+    } else {
+        ' '
+    }
+}
+
+fn line(w: &mut impl std::io::Write, s: &str) -> crate::Result {
+    write!(w, "{s}\r\n")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn write() -> crate::Result {
+        let statement = crate::statement::test::statement(crate::statement::Granularity::Payout);
+
+        let mut mt940 = Vec::new();
+        crate::statement::mt940::write(&statement, &mut mt940)?;
+
+        assert_eq!(
+            String::from_utf8(mt940).unwrap(),
+            include_str!("../../fixtures/statement/mt940-payout.txt")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_transactions() -> crate::Result {
+        let statement =
+            crate::statement::test::statement(crate::statement::Granularity::Transaction);
+
+        let mut mt940 = Vec::new();
+        crate::statement::mt940::write(&statement, &mut mt940)?;
+
+        let mt940 = String::from_utf8(mt940).unwrap();
+        assert_eq!(
+            mt940,
+            include_str!("../../fixtures/statement/mt940-transaction.txt")
+        );
+        assert!(mt940.lines().all(|x| x.len() <= super::LINE_LENGTH));
+
+        Ok(())
+    }
+
+    /**
+     * Checks the tags, their order, the format of the fields and the lines against the MT940
+     * rules.
+     */
+    fn validate(mt940: &str) -> Result<(), String> {
+        let charset = regex::Regex::new(r"^[A-Za-z0-9/\-?:().,'+ ]*$").unwrap();
+        let amount = r"(\d{1,12},\d{0,2})";
+        let balance = format!(r"^[CD]\d{{6}}[A-Z]{{3}}{amount}$");
+        let formats = [
+            ("20", r"^[^\n]{1,16}$".to_string()),
+            ("25", r"^[^\n]{1,35}$".to_string()),
+            ("28C", r"^\d{1,5}(/\d{1,5})?$".to_string()),
+            ("60F", balance.clone()),
+            ("60M", balance.clone()),
+            (
+                "61",
+                format!(
+                    r"^\d{{6}}(\d{{4}})?R?[CD][A-Z]?{amount}[SNF][A-Z0-9]{{3}}[^/\n]{{1,16}}(//[^\n]{{1,16}})?(\n[^\n]{{1,34}})?$"
+                ),
+            ),
+            ("86", r"^[^\n]{1,65}(\n[^\n]{1,65}){0,5}$".to_string()),
+            ("62F", balance.clone()),
+            ("62M", balance.clone()),
+            ("64", balance.clone()),
+            ("65", balance),
+        ];
+        let order = regex::Regex::new(r"^20 25 28C 60[FM] (61 (86 )?)*62[FM] (64 )?(65 )*(86 )?-$")
+            .unwrap();
+
+        let lines = mt940
+            .strip_suffix("\r\n")
+            .ok_or("missing final CRLF")?
+            .split("\r\n")
+            .collect::<Vec<_>>();
+
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        for line in &lines {
+            if line.len() > super::LINE_LENGTH {
+                return Err(format!("{line:?} longer than {}", super::LINE_LENGTH));
+            }
+            if !charset.is_match(line) {
+                return Err(format!("{line:?} out of the SWIFT character set"));
+            }
+
+            if *line == "-" {
+                fields.push(("-", String::new()));
+            } else if let Some((tag, value)) =
+                line.strip_prefix(':').and_then(|x| x.split_once(':'))
+            {
+                fields.push((tag, value.to_string()));
+            } else if line.starts_with([':', '-']) {
+                return Err(format!("{line:?} is neither a field nor the end"));
+            } else {
+                let (_, value) = fields
+                    .last_mut()
+                    .ok_or_else(|| format!("{line:?} out of a field"))?;
+                value.push('\n');
+                value.push_str(line);
+            }
+        }
+
+        let tags = fields.iter().map(|(x, _)| *x).collect::<Vec<_>>().join(" ");
+        if !order.is_match(&tags) {
+            return Err(format!("unexpected tags {tags}"));
+        }
+
+        for (tag, value) in &fields {
+            let Some((_, format)) = formats.iter().find(|(x, _)| x == tag) else {
+                continue;
+            };
+            if !regex::Regex::new(format).unwrap().is_match(value) {
+                return Err(format!(":{tag}:{value:?} breaks the field format"));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules() -> crate::Result {
+        let mut edge = crate::statement::test::statement(crate::statement::Granularity::Payout);
+        edge.id = "STATEMENT_2021_01&".repeat(2);
+        edge.account.id = String::new();
+        edge.entries[0].reference = "PO/0107".to_string();
+        edge.entries[0].description = format!("{}:{}", "é_".repeat(61), "-x".repeat(200));
+
+        for statement in [
+            crate::statement::test::statement(crate::statement::Granularity::Payout),
+            crate::statement::test::statement(crate::statement::Granularity::Transaction),
+            edge,
+        ] {
+            let mut mt940 = Vec::new();
+            crate::statement::mt940::write(&statement, &mut mt940)?;
+
+            validate(&String::from_utf8(mt940).unwrap()).unwrap();
+        }
+
+        let mt940 = include_str!("../../fixtures/statement/mt940-payout.txt");
+        assert!(validate(&mt940.replace(":28C:00001/001\r\n", "")).is_err());
+        assert!(validate(&mt940.replace(":62F:", ":62X:")).is_err());
+        assert!(validate(&mt940.replace("NMSC", "NMSC_")).is_err());
+        assert!(validate(&mt940.replace(":86:", ":86:x\r\n-x")).is_err());
+        assert!(validate(&mt940.replace(":86:", &format!(":86:{}", "x".repeat(62)))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse() -> crate::Result {
+        let statements = crate::statement::mt940::parse(include_str!(
+            "../../fixtures/statement/mt940-payout.txt"
+        ))?;
+
+        assert_eq!(statements.len(), 1);
+
+        let statement = &statements[0];
+        assert_eq!(statement.id, "STMT-2021-01");
+        assert_eq!(statement.account.id, "DE89370400440532013000");
+        assert_eq!(statement.account.currency, "EUR");
+        assert_eq!(statement.from_date, "2021-01-01");
+        assert_eq!(statement.to_date, "2021-01-31");
+        assert_eq!(statement.entries.len(), 1);
+        assert_eq!(statement.entries[0].reference, "PO-0107");
+        assert_eq!(statement.entries[0].booking_date, "2021-01-07");
+        assert!((statement.closing_balance() - 129.18).abs() < 0.001);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_bank() -> crate::Result {
+        let mt940 = "{1:F01COBADEFFAXXX0000000000}{2:O940COBADEFFXXXXN}{4:\r\n\
+            :20:053D20210131\r\n\
+            :25:37040044/0532013000\r\n\
+            :28C:21/1\r\n\
+            :60F:C201231EUR1000,00\r\n\
+            :61:2101080108CR29,18NTRFNONREF//2021010812345678\r\n\
+            :86:166?00GUTSCHRIFT?20SUMUP PAYOUT ?21PO-0107?32SumUp Payments\r\n\
+            ?33 Limited\r\n\
+            :61:2012311231DR45,00NTRFNONREF\r\n\
+            :86:Coffee beans supplier\r\n\
+            :62F:C210131EUR984,18\r\n\
+            -}";
+
+        let statements = crate::statement::mt940::parse(mt940)?;
+
+        assert_eq!(statements.len(), 1);
+
+        let statement = &statements[0];
+        assert_eq!(statement.opening_balance, 1000.);
+        assert_eq!(statement.entries.len(), 2);
+        assert_eq!(statement.entries[0].amount, 29.18);
+        assert_eq!(
+            statement.entries[0].description,
+            "SUMUP PAYOUT PO-0107 SumUp Payments Limited"
+        );
+        assert_eq!(statement.entries[1].amount, -45.);
+        assert_eq!(statement.entries[1].description, "Coffee beans supplier");
+
+        for booking in ["1301", "0132", "0230"] {
+            assert!(
+                super::parse_transaction(&format!("210108{booking}CR29,18NTRFNONREF")).is_err()
+            );
+        }
+
+        Ok(())
+    }
+}
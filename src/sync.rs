@@ -0,0 +1,222 @@
+/**
+ * Position of the last successful synchronization.
+ */
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Checkpoint {
+    /** Sent as `changes_since` on the next run */
+    pub changes_since: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Change {
+    /** The transaction was created after the previous checkpoint */
+    Inserted,
+    /** The transaction already existed and changed since, for example refunded or charged back */
+    Updated,
+}
+
+/**
+ * Receives the synchronized transactions.
+ */
+pub trait Sink {
+    fn insert(&mut self, transaction: &crate::Transaction) -> crate::Result;
+
+    fn update(&mut self, transaction: &crate::Transaction) -> crate::Result;
+}
+
+/**
+ * Persists the checkpoint between two runs.
+ */
+pub trait CheckpointStore {
+    fn load(&mut self) -> crate::Result<Option<Checkpoint>>;
+
+    fn save(&mut self, checkpoint: &Checkpoint) -> crate::Result;
+}
+
+/**
+ * Stores the checkpoint as JSON in a file.
+ */
+#[derive(Clone, Debug)]
+pub struct FileCheckpointStore {
+    path: std::path::PathBuf,
+}
+
+impl FileCheckpointStore {
+    #[must_use]
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn load(&mut self) -> crate::Result<Option<Checkpoint>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&mut self, checkpoint: &Checkpoint) -> crate::Result {
+        std::fs::write(&self.path, serde_json::to_string(checkpoint)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    pub inserted: usize,
+    pub updated: usize,
+    pub checkpoint: Checkpoint,
+}
+
+/**
+ * Keeps a local copy of the transactions history up to date.
+ *
+ * Each run only fetches the transactions changed since the previous checkpoint, forwards them to
+ * the sink and saves the new checkpoint once every page has been processed. If the run fails, the
+ * previous checkpoint is kept and the next run starts over from it.
+ */
+#[derive(Clone, Debug)]
+pub struct Synchronizer<'a> {
+    transactions: crate::services::Transactions<'a>,
+    /** Lower bound of the first synchronization, when there is no checkpoint yet */
+    pub start_date: Option<String>,
+    pub page_size: u32,
+}
+
+impl<'a> Synchronizer<'a> {
+    #[must_use]
+    pub fn new(transactions: crate::services::Transactions<'a>) -> Self {
+        Self {
+            transactions,
+            start_date: None,
+            page_size: 100,
+        }
+    }
+
+    pub fn run(
+        &self,
+        sink: &mut impl Sink,
+        store: &mut impl CheckpointStore,
+    ) -> crate::Result<Report> {
+        let previous = store.load()?;
+        let checkpoint = Checkpoint {
+            changes_since: crate::date::now_rfc3339(),
+        };
+
+        let filter = crate::services::transactions::Filter {
+            start_date: self.start_date.clone().unwrap_or_default(),
+            limit: Some(self.page_size),
+            changes_since: previous.as_ref().map(|x| x.changes_since.clone()),
+
+            ..Default::default()
+        };
+
+        let mut report = Report::default();
+
+        for page in self.transactions.pages(&filter) {
+            for transaction in &page? {
+                match change(transaction, previous.as_ref()) {
+                    Change::Inserted => {
+                        sink.insert(transaction)?;
+                        report.inserted += 1;
+                    }
+                    Change::Updated => {
+                        sink.update(transaction)?;
+                        report.updated += 1;
+                    }
+                }
+            }
+        }
+
+        store.save(&checkpoint)?;
+        report.checkpoint = checkpoint;
+
+        Ok(report)
+    }
+}
+
+fn change(transaction: &crate::Transaction, previous: Option<&Checkpoint>) -> Change {
+    match previous {
+        Some(checkpoint) if transaction.timestamp < checkpoint.changes_since => Change::Updated,
+        _ => Change::Inserted,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[derive(Default)]
+    struct Memory {
+        transactions: std::collections::HashMap<String, crate::Transaction>,
+        checkpoint: Option<crate::sync::Checkpoint>,
+    }
+
+    impl crate::sync::Sink for Memory {
+        fn insert(&mut self, transaction: &crate::Transaction) -> crate::Result {
+            self.transactions
+                .insert(transaction.id.clone(), transaction.clone());
+
+            Ok(())
+        }
+
+        fn update(&mut self, transaction: &crate::Transaction) -> crate::Result {
+            self.insert(transaction)
+        }
+    }
+
+    impl crate::sync::CheckpointStore for Memory {
+        fn load(&mut self) -> crate::Result<Option<crate::sync::Checkpoint>> {
+            Ok(self.checkpoint.clone())
+        }
+
+        fn save(&mut self, checkpoint: &crate::sync::Checkpoint) -> crate::Result {
+            self.checkpoint = Some(checkpoint.clone());
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn change() {
+        let transaction: crate::Transaction =
+            serde_json::from_str(include_str!("../fixtures/transaction.json")).unwrap();
+
+        let before = crate::sync::Checkpoint {
+            changes_since: "2021-01-01T00:00:00Z".to_string(),
+        };
+        let after = crate::sync::Checkpoint {
+            changes_since: "2021-02-01T00:00:00Z".to_string(),
+        };
+
+        assert_eq!(
+            super::change(&transaction, None),
+            crate::sync::Change::Inserted
+        );
+        assert_eq!(
+            super::change(&transaction, Some(&before)),
+            crate::sync::Change::Inserted
+        );
+        assert_eq!(
+            super::change(&transaction, Some(&after)),
+            crate::sync::Change::Updated
+        );
+    }
+
+    #[test]
+    fn run() -> crate::Result {
+        let api = crate::test::api()?;
+        let mut sink = Memory::default();
+        let mut store = Memory::default();
+
+        let mut synchronizer = crate::sync::Synchronizer::new(api.transactions());
+        synchronizer.start_date = Some("2021-01-01".to_string());
+
+        let report = synchronizer.run(&mut sink, &mut store)?;
+        assert_eq!(store.checkpoint, Some(report.checkpoint));
+        assert_eq!(sink.transactions.len(), report.inserted + report.updated);
+
+        Ok(())
+    }
+}
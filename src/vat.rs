@@ -0,0 +1,368 @@
+/**
+ * Totals of one VAT rate, refunds and chargebacks already deducted.
+ */
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct Rate {
+    /** `0.19` for 19 % */
+    pub vat_rate: f32,
+    pub net: f32,
+    pub vat: f32,
+    pub gross: f32,
+    /** Gross amount refunded or charged back during the period, included in `gross` */
+    pub refunded: f32,
+    /** Number of sales */
+    pub transactions: usize,
+}
+
+impl Rate {
+    fn add(&mut self, net: f32, vat: f32, gross: f32) {
+        self.net += net;
+        self.vat += vat;
+        self.gross += gross;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct Report {
+    /** First day of the period, inclusive `YYYY-MM-DD` */
+    pub from_date: String,
+    /** Last day of the period, inclusive `YYYY-MM-DD` */
+    pub to_date: String,
+    pub currency: String,
+    /** Sorted by descending rate */
+    pub rates: Vec<Rate>,
+    pub net: f32,
+    pub vat: f32,
+    pub gross: f32,
+}
+
+impl Report {
+    /**
+     * Aggregates the sales made during the period and the refunds and chargebacks booked during
+     * it, whatever the date of the refunded sale.
+     *
+     * The amounts come from the products when there are some: `total_price`, `vat_amount` and
+     * `total_with_vat` per `vat_rate`. The part of the transaction amount not covered by the
+     * products, like tips, is reported with a rate of 0. Transactions without product are split
+     * using their `vat_amount`, transactions with `tax_enabled` false have no VAT. A refund is
+     * spread over the rates in proportion of the refunded amount, failed refunds and chargebacks
+     * are ignored.
+     */
+    #[must_use]
+    pub fn new(from_date: &str, to_date: &str, transactions: &[crate::Transaction]) -> Self {
+        let mut report = Self {
+            from_date: from_date.to_string(),
+            to_date: to_date.to_string(),
+
+            ..Default::default()
+        };
+
+        let in_period = |timestamp: &str| {
+            let day = timestamp.get(..10).unwrap_or(timestamp);
+
+            day >= from_date && day <= to_date
+        };
+
+        for transaction in transactions {
+            if matches!(
+                transaction.status,
+                crate::Status::Cancelled | crate::Status::Failed
+            ) {
+                continue;
+            }
+
+            if report.currency.is_empty() {
+                report.currency.clone_from(&transaction.currency);
+            }
+
+            let split = split(transaction);
+
+            if in_period(&transaction.timestamp) {
+                for (vat_rate, net, vat, gross) in &split {
+                    let rate = report.rate(*vat_rate);
+                    rate.add(*net, *vat, *gross);
+                    rate.transactions += 1;
+                }
+            }
+
+            let refunds = transaction
+                .events
+                .iter()
+                .filter(|x| x.transaction_id == transaction.id)
+                .filter(|x| matches!(x.ty.as_str(), "REFUND" | "CHARGE_BACK"))
+                .filter(|x| x.status != "FAILED")
+                .filter(|x| in_period(&x.timestamp));
+
+            for refund in refunds {
+                if transaction.amount == 0. {
+                    continue;
+                }
+
+                let ratio = refund.amount.abs() / transaction.amount;
+
+                for (vat_rate, net, vat, gross) in &split {
+                    let rate = report.rate(*vat_rate);
+                    rate.add(-net * ratio, -vat * ratio, -gross * ratio);
+                    rate.refunded += gross * ratio;
+                }
+            }
+        }
+
+        report
+            .rates
+            .sort_by(|a, b| b.vat_rate.total_cmp(&a.vat_rate));
+        report.net = report.rates.iter().map(|x| x.net).sum();
+        report.vat = report.rates.iter().map(|x| x.vat).sum();
+        report.gross = report.rates.iter().map(|x| x.gross).sum();
+
+        report
+    }
+
+    /**
+     * Fetches the transactions changed since the start of the period, so refunds of older sales
+     * are included. The history lacks the events and products, each transaction is then fetched
+     * by id.
+     */
+    pub fn fetch(
+        transactions: &crate::services::Transactions,
+        from_date: &str,
+        to_date: &str,
+    ) -> crate::Result<Self> {
+        let filter = crate::services::transactions::Filter {
+            changes_since: Some(format!("{from_date}T00:00:00Z")),
+
+            ..Default::default()
+        };
+
+        let mut history = Vec::new();
+        for page in transactions.pages(&filter) {
+            for transaction in page? {
+                if matches!(
+                    transaction.status,
+                    crate::Status::Cancelled | crate::Status::Failed
+                ) || history
+                    .iter()
+                    .any(|x: &crate::Transaction| x.id == transaction.id)
+                {
+                    continue;
+                }
+
+                history.push(transactions.find_by_id(&transaction.id)?);
+            }
+        }
+
+        Ok(Self::new(from_date, to_date, &history))
+    }
+
+    /**
+     * One row per rate followed by a `total` row.
+     */
+    pub fn write_csv(
+        &self,
+        w: &mut impl std::io::Write,
+        options: &crate::export::csv::Options,
+    ) -> crate::Result {
+        let delimiter = options.delimiter.to_string();
+        let number =
+            |n: f32| format!("{n:.2}").replace('.', &options.decimal_separator.to_string());
+
+        if options.header {
+            let header = [
+                "vat_rate",
+                "net",
+                "vat",
+                "gross",
+                "refunded",
+                "transactions",
+            ];
+            writeln!(w, "{}", header.join(&delimiter))?;
+        }
+
+        for rate in &self.rates {
+            let row = [
+                number(rate.vat_rate * 100.),
+                number(rate.net),
+                number(rate.vat),
+                number(rate.gross),
+                number(rate.refunded),
+                rate.transactions.to_string(),
+            ];
+            writeln!(w, "{}", row.join(&delimiter))?;
+        }
+
+        let total = [
+            "total".to_string(),
+            number(self.net),
+            number(self.vat),
+            number(self.gross),
+            number(self.rates.iter().map(|x| x.refunded).sum()),
+            String::new(),
+        ];
+        writeln!(w, "{}", total.join(&delimiter))?;
+
+        Ok(())
+    }
+
+    fn rate(&mut self, vat_rate: f32) -> &mut Rate {
+        let index = self
+            .rates
+            .iter()
+            .position(|x| (x.vat_rate - vat_rate).abs() < 0.0001)
+            .unwrap_or_else(|| {
+                self.rates.push(Rate {
+                    vat_rate,
+
+                    ..Default::default()
+                });
+                self.rates.len() - 1
+            });
+
+        &mut self.rates[index]
+    }
+}
+
+/**
+ * `(vat_rate, net, vat, gross)` parts of the transaction amount.
+ */
+fn split(transaction: &crate::Transaction) -> Vec<(f32, f32, f32, f32)> {
+    let mut parts = Vec::<(f32, f32, f32, f32)>::new();
+
+    let mut add = |vat_rate: f32, net: f32, vat: f32, gross: f32| match parts
+        .iter_mut()
+        .find(|x| (x.0 - vat_rate).abs() < 0.0001)
+    {
+        Some(part) => {
+            part.1 += net;
+            part.2 += vat;
+            part.3 += gross;
+        }
+        None => parts.push((vat_rate, net, vat, gross)),
+    };
+
+    let mut covered = 0.;
+
+    if !transaction.tax_enabled {
+        // Everything falls in the remainder below
+    } else if transaction.products.is_empty() {
+        let gross = transaction.amount - transaction.tip_amount;
+        let net = gross - transaction.vat_amount;
+        let vat_rate = match transaction.vat_rates.as_slice() {
+            [vat_rate] => *vat_rate,
+            _ if net != 0. => (transaction.vat_amount / net * 100.).round() / 100.,
+            _ => 0.,
+        };
+
+        add(vat_rate, net, transaction.vat_amount, gross);
+        covered = gross;
+    } else {
+        for product in &transaction.products {
+            add(
+                product.vat_rate,
+                product.total_price,
+                product.vat_amount,
+                product.total_with_vat,
+            );
+            covered += product.total_with_vat;
+        }
+    }
+
+    let remainder = transaction.amount - covered;
+    if remainder.abs() >= 0.005 {
+        add(0., remainder, 0., remainder);
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn report() {
+        let transaction = crate::export::test::transaction();
+
+        let report = crate::vat::Report::new("2021-01-01", "2021-01-31", &[transaction]);
+
+        assert_eq!(report.rates.len(), 2);
+
+        let standard = &report.rates[0];
+        assert_eq!(standard.vat_rate, 0.19);
+        assert_eq!(standard.transactions, 1);
+        assert!((standard.gross - 5.8 * (1. - 3.96 / 9.76)).abs() < 0.001);
+
+        let reduced = &report.rates[1];
+        assert_eq!(reduced.vat_rate, 0.07);
+        assert!((reduced.refunded - 3.96 * 3.96 / 9.76).abs() < 0.001);
+
+        assert!((report.gross - 5.8).abs() < 0.001);
+        assert!((report.net + report.vat - report.gross).abs() < 0.001);
+    }
+
+    #[test]
+    fn refund_next_period() {
+        let transaction = crate::export::test::transaction();
+
+        let report = crate::vat::Report::new("2021-01-06", "2021-01-31", &[transaction]);
+
+        assert_eq!(report.rates[0].transactions, 0);
+        assert!((report.gross + 3.96).abs() < 0.001);
+    }
+
+    #[test]
+    fn failed_refund() {
+        let mut transaction = crate::export::test::transaction();
+        transaction.events.last_mut().unwrap().status = "FAILED".to_string();
+
+        let report = crate::vat::Report::new("2021-01-01", "2021-01-31", &[transaction]);
+
+        assert!((report.gross - 9.76).abs() < 0.001);
+        assert!(report.rates.iter().all(|x| x.refunded == 0.));
+    }
+
+    #[test]
+    fn write_csv() -> crate::Result {
+        let mut transaction = crate::export::test::transaction();
+        transaction.events.clear();
+
+        let report = crate::vat::Report::new("2021-01-01", "2021-01-31", &[transaction]);
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv, &crate::export::csv::Options::locale("de-DE"))?;
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "vat_rate;net;vat;gross;refunded;transactions
+19,00;5,00;0,80;5,80;0,00;1
+7,00;3,70;0,26;3,96;0,00;1
+total;8,70;1,06;9,76;0,00;
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fetch() -> crate::Result {
+        let api = crate::test::api()?;
+
+        crate::vat::Report::fetch(&api.transactions(), "2021-01-01", "2021-03-31")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_events() -> crate::Result {
+        let server = crate::mock::Server::start()?;
+        let api = crate::SumUp::from(server.config())?;
+
+        api.transactions().refund(crate::mock::TRANSACTION_ID, 1.)?;
+
+        let today = crate::date::now_rfc3339();
+        let today = &today[..10];
+        let report = crate::vat::Report::fetch(&api.transactions(), today, today)?;
+
+        assert!((report.gross + 1.).abs() < 0.001);
+        assert!((report.rates.iter().map(|x| x.refunded).sum::<f32>() - 1.).abs() < 0.001);
+
+        Ok(())
+    }
+}
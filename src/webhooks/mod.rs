@@ -0,0 +1,132 @@
+#[cfg(feature = "webhook-receiver")]
+pub mod receiver;
+
+/**
+ * Notification posted to the checkout `return_url`.
+ *
+ * Notifications aren't signed, anyone knowing the URL can post one: [`verify`] them before
+ * acting on them.
+ */
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+pub enum Event {
+    CheckoutStatusChanged {
+        /** Checkout id */
+        id: String,
+    },
+    /** Event type this crate doesn't know yet */
+    Unknown {
+        event_type: String,
+        id: Option<String>,
+    },
+}
+
+impl Event {
+    /** Id of the resource the event is about */
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Self::CheckoutStatusChanged { id } => Some(id),
+            Self::Unknown { id, .. } => id.as_deref(),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Notification {
+    event_type: String,
+    id: Option<String>,
+}
+
+/**
+ * Parses a notification body: `{"event_type": "CHECKOUT_STATUS_CHANGED", "id": "…"}`.
+ */
+pub fn parse(body: &[u8]) -> crate::Result<Event> {
+    let notification = serde_json::from_slice::<Notification>(body)?;
+
+    let event = match (notification.event_type.as_str(), notification.id) {
+        ("CHECKOUT_STATUS_CHANGED", Some(id)) if !id.is_empty() => {
+            Event::CheckoutStatusChanged { id }
+        }
+        ("CHECKOUT_STATUS_CHANGED", _) => {
+            return Err(crate::Error::InvalidWebhook(
+                "checkout status change without checkout id".to_string(),
+            ));
+        }
+        (_, id) => Event::Unknown {
+            event_type: notification.event_type,
+            id,
+        },
+    };
+
+    Ok(event)
+}
+
+/**
+ * Event confirmed by the API.
+ */
+#[derive(Clone, Debug)]
+pub enum Verified {
+    /** The checkout as currently known by SumUp, its `status` is the trustworthy one */
+    Checkout(crate::Checkout),
+}
+
+/**
+ * Confirms the event by fetching the resource it is about through the API.
+ *
+ * Unknown events can't be confirmed and are rejected with [`crate::Error::InvalidWebhook`].
+ */
+pub fn verify(checkouts: &crate::services::Checkout, event: &Event) -> crate::Result<Verified> {
+    match event {
+        Event::CheckoutStatusChanged { id } => {
+            let checkout = checkouts.find_by_id(id)?;
+
+            if checkout.id.as_deref() != Some(id.as_str()) {
+                return Err(crate::Error::InvalidWebhook(format!(
+                    "checkout {id} not found"
+                )));
+            }
+
+            Ok(Verified::Checkout(checkout))
+        }
+        Event::Unknown { event_type, .. } => Err(crate::Error::InvalidWebhook(format!(
+            "unknown event type {event_type}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn parse() -> crate::Result {
+        let event = crate::webhooks::parse(
+            br#"{"event_type": "CHECKOUT_STATUS_CHANGED", "id": "4e425463-3e1b-431d-83fa-1e51c2925e99"}"#,
+        )?;
+
+        assert_eq!(
+            event,
+            crate::webhooks::Event::CheckoutStatusChanged {
+                id: "4e425463-3e1b-431d-83fa-1e51c2925e99".to_string()
+            }
+        );
+
+        let event = crate::webhooks::parse(br#"{"event_type": "PAYOUT_SENT", "id": "42"}"#)?;
+        assert_eq!(event.id(), Some("42"));
+
+        assert!(crate::webhooks::parse(br#"{"event_type": "CHECKOUT_STATUS_CHANGED"}"#).is_err());
+        assert!(crate::webhooks::parse(b"id=42").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify() -> crate::Result {
+        let api = crate::test::api()?;
+
+        let event = crate::webhooks::Event::CheckoutStatusChanged {
+            id: crate::mock::CHECKOUT_ID.to_string(),
+        };
+        crate::webhooks::verify(&api.checkout(), &event)?;
+
+        Ok(())
+    }
+}
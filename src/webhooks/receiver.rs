@@ -0,0 +1,536 @@
+/** Number of delivered events remembered to drop duplicates */
+const SEEN_CAPACITY: usize = 1_024;
+
+/** Largest accepted request head or body */
+const MAX_SIZE: usize = 64 * 1_024;
+
+/** Connections handled at the same time by [`Receiver::serve`] */
+const WORKERS: usize = 4;
+
+/** Read and write timeout of the served connections */
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+/**
+ * Business logic run for each verified event.
+ */
+pub trait Handler {
+    fn handle(&self, event: &super::Verified) -> std::result::Result<(), HandlerError>;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&super::Verified) -> std::result::Result<(), HandlerError>,
+{
+    fn handle(&self, event: &super::Verified) -> std::result::Result<(), HandlerError> {
+        self(event)
+    }
+}
+
+/**
+ * Confirms events, [`crate::services::Checkout`] does it with [`super::verify`].
+ */
+pub trait Verifier {
+    fn verify(&self, event: &super::Event) -> crate::Result<super::Verified>;
+}
+
+impl Verifier for crate::services::Checkout<'_> {
+    fn verify(&self, event: &super::Event) -> crate::Result<super::Verified> {
+        super::verify(self, event)
+    }
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    /** The handler accepted the event */
+    Dispatched,
+    /** The same event, in the same state, was already dispatched */
+    Duplicate,
+    /** The event type is unknown, there is nothing to verify nor dispatch */
+    Ignored,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid notification: {0}")]
+    Invalid(crate::Error),
+    #[error("Verification failed: {0}")]
+    Verification(crate::Error),
+    #[error("Handler failed after {attempts} attempts: {source}")]
+    Handler { attempts: u32, source: HandlerError },
+}
+
+impl Error {
+    /**
+     * HTTP status to answer, SumUp retries the notification on server errors.
+     */
+    #[must_use]
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Invalid(_) => 400,
+            Self::Verification(_) => 502,
+            Self::Handler { .. } => 500,
+        }
+    }
+}
+
+/**
+ * Framework agnostic notification endpoint: parses, verifies, deduplicates and dispatches.
+ *
+ * [`Self::handle`] retries failing handlers `retries` times, waiting `retry_delay` then twice as
+ * long before each new attempt. An event is claimed before its handler runs, so concurrent
+ * deliveries of the same event dispatch it once, and released if the handler finally fails.
+ */
+pub struct Receiver<V: Verifier, H: Handler> {
+    verifier: V,
+    handler: H,
+    pub retries: u32,
+    pub retry_delay: std::time::Duration,
+    seen: std::sync::Mutex<std::collections::VecDeque<String>>,
+}
+
+impl<V: Verifier, H: Handler> Receiver<V, H> {
+    pub fn new(verifier: V, handler: H) -> Self {
+        Self {
+            verifier,
+            handler,
+            retries: 2,
+            retry_delay: std::time::Duration::from_millis(200),
+            seen: std::sync::Mutex::new(std::collections::VecDeque::new()),
+        }
+    }
+
+    /**
+     * Handles a notification body.
+     */
+    pub fn handle(&self, body: &[u8]) -> Result<Outcome, Error> {
+        self.dispatch(body, self.retries)
+    }
+
+    fn dispatch(&self, body: &[u8], retries: u32) -> Result<Outcome, Error> {
+        let event = super::parse(body).map_err(Error::Invalid)?;
+
+        if matches!(event, super::Event::Unknown { .. }) {
+            log::debug!("Ignoring webhook {event:?}");
+            return Ok(Outcome::Ignored);
+        }
+
+        let verified = self.verifier.verify(&event).map_err(Error::Verification)?;
+        let key = key(&verified);
+
+        {
+            let mut seen = self.seen();
+            if seen.contains(&key) {
+                return Ok(Outcome::Duplicate);
+            }
+            if seen.len() == SEEN_CAPACITY {
+                seen.pop_front();
+            }
+            seen.push_back(key.clone());
+        }
+
+        let mut delay = self.retry_delay;
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            match self.handler.handle(&verified) {
+                Ok(()) => break,
+                Err(err) if attempts <= retries => {
+                    log::warn!("Webhook handler failed (attempt {attempts}): {err}");
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(source) => {
+                    self.seen().retain(|x| *x != key);
+
+                    return Err(Error::Handler { attempts, source });
+                }
+            }
+        }
+
+        Ok(Outcome::Dispatched)
+    }
+
+    /**
+     * Serves the notifications posted on `path` with a few worker threads, forever.
+     *
+     * Failing accepts and connections are logged and skipped. Handlers are not retried in
+     * place, the error status makes SumUp post the notification again later.
+     */
+    pub fn serve(&self, listener: &std::net::TcpListener, path: &str)
+    where
+        V: Sync,
+        H: Sync,
+    {
+        std::thread::scope(|scope| {
+            for _ in 0..WORKERS {
+                scope.spawn(|| {
+                    for stream in listener.incoming() {
+                        let mut stream = match stream {
+                            Ok(stream) => stream,
+                            Err(err) => {
+                                log::warn!("Webhook accept failed: {err}");
+                                continue;
+                            }
+                        };
+
+                        let result = stream
+                            .set_read_timeout(Some(TIMEOUT))
+                            .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+                            .map_err(crate::Error::from)
+                            .and_then(|()| self.connection(&mut stream, path));
+                        if let Err(err) = result {
+                            log::warn!("Webhook connection failed: {err}");
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /**
+     * Reads one HTTP/1.1 request and writes its response, without retrying the handler.
+     */
+    pub fn connection(
+        &self,
+        stream: &mut (impl std::io::Read + std::io::Write),
+        path: &str,
+    ) -> crate::Result {
+        let status = match request(stream)? {
+            Ok(Request { method, target, .. }) if target.split('?').next() != Some(path) => {
+                log::debug!("Webhook {method} {target} not found");
+                404
+            }
+            Ok(Request { method, .. }) if method != "POST" => 405,
+            Ok(Request { body, .. }) => match self.dispatch(&body, 0) {
+                Ok(_) => 200,
+                Err(err) => {
+                    log::warn!("{err}");
+                    err.status()
+                }
+            },
+            Err(status) => status,
+        };
+
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Content Too Large",
+            502 => "Bad Gateway",
+            _ => "Internal Server Error",
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {status} {reason}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )?;
+        stream.flush()?;
+
+        Ok(())
+    }
+
+    fn seen(&self) -> std::sync::MutexGuard<'_, std::collections::VecDeque<String>> {
+        self.seen
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn key(verified: &super::Verified) -> String {
+    match verified {
+        super::Verified::Checkout(checkout) => format!(
+            "checkout:{}:{}",
+            checkout.id.as_deref().unwrap_or_default(),
+            checkout.status.as_deref().unwrap_or_default()
+        ),
+    }
+}
+
+struct Request {
+    method: String,
+    target: String,
+    body: Vec<u8>,
+}
+
+/**
+ * Reads a request, or the error status to answer.
+ *
+ * Bodies must have a `Content-Length`, chunked ones are refused.
+ */
+fn request(stream: &mut impl std::io::Read) -> crate::Result<Result<Request, u16>> {
+    let mut data = Vec::new();
+    let mut buffer = [0; 4_096];
+
+    let head_end = loop {
+        if let Some(end) = data.windows(4).position(|x| x == b"\r\n\r\n") {
+            break end;
+        }
+        if data.len() > MAX_SIZE {
+            return Ok(Err(413));
+        }
+
+        let n = stream.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(Err(400));
+        }
+        data.extend_from_slice(&buffer[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Ok(Err(400));
+    };
+
+    let headers = lines
+        .filter_map(|x| x.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+        .collect::<Vec<_>>();
+    let header = |name: &str| headers.iter().find(|(x, _)| x == name).map(|(_, x)| *x);
+
+    if header("transfer-encoding").is_some() {
+        return Ok(Err(411));
+    }
+    let length = match header("content-length").map(str::parse::<usize>) {
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Err(400)),
+        None if method == "POST" => return Ok(Err(411)),
+        None => 0,
+    };
+
+    if length > MAX_SIZE {
+        return Ok(Err(413));
+    }
+
+    let mut body = data.split_off(head_end + 4);
+    while body.len() < length {
+        let n = stream.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(Err(400));
+        }
+        body.extend_from_slice(&buffer[..n]);
+    }
+    body.truncate(length);
+
+    Ok(Ok(Request {
+        method: method.to_string(),
+        target: target.to_string(),
+        body,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::webhooks::receiver::Outcome;
+
+    struct Verifier;
+
+    impl crate::webhooks::receiver::Verifier for Verifier {
+        fn verify(
+            &self,
+            event: &crate::webhooks::Event,
+        ) -> crate::Result<crate::webhooks::Verified> {
+            let checkout = crate::Checkout {
+                id: event.id().map(ToString::to_string),
+                status: Some("PAID".to_string()),
+
+                ..Default::default()
+            };
+
+            Ok(crate::webhooks::Verified::Checkout(checkout))
+        }
+    }
+
+    const BODY: &[u8] = br#"{"event_type": "CHECKOUT_STATUS_CHANGED", "id": "42"}"#;
+
+    #[test]
+    fn handle() {
+        let calls = std::cell::Cell::new(0);
+        let handler = |_: &crate::webhooks::Verified| -> Result<(), _> {
+            calls.set(calls.get() + 1);
+
+            if calls.get() == 1 {
+                Err("database unavailable".into())
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut receiver = crate::webhooks::receiver::Receiver::new(Verifier, handler);
+        receiver.retry_delay = std::time::Duration::ZERO;
+
+        assert!(matches!(receiver.handle(BODY), Ok(Outcome::Dispatched)));
+        assert_eq!(calls.get(), 2);
+        assert!(matches!(receiver.handle(BODY), Ok(Outcome::Duplicate)));
+        assert_eq!(calls.get(), 2);
+        assert!(matches!(
+            receiver.handle(br#"{"event_type": "OTHER"}"#),
+            Ok(Outcome::Ignored)
+        ));
+        assert_eq!(receiver.handle(b"{").unwrap_err().status(), 400);
+    }
+
+    #[test]
+    fn handler_failure() {
+        let handler = |_: &crate::webhooks::Verified| -> Result<(), _> { Err("failure".into()) };
+
+        let mut receiver = crate::webhooks::receiver::Receiver::new(Verifier, handler);
+        receiver.retry_delay = std::time::Duration::ZERO;
+
+        let err = receiver.handle(BODY).unwrap_err();
+        assert!(matches!(
+            err,
+            crate::webhooks::receiver::Error::Handler { attempts: 3, .. }
+        ));
+        assert_eq!(err.status(), 500);
+    }
+
+    #[test]
+    fn connection() -> crate::Result {
+        let handler = |_: &crate::webhooks::Verified| Ok(());
+        let receiver = crate::webhooks::receiver::Receiver::new(Verifier, handler);
+
+        let request = format!(
+            "POST /webhooks/sumup HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            BODY.len(),
+            String::from_utf8_lossy(BODY)
+        );
+        let mut stream = Stream::new(request.as_bytes());
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert!(stream.output.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+        let mut stream = Stream::new(b"GET /other HTTP/1.1\r\n\r\n");
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert!(stream.output.starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+
+        let mut stream = Stream::new(
+            b"POST /webhooks/sumup HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\n0\r\n\r\n",
+        );
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert!(
+            stream
+                .output
+                .starts_with(b"HTTP/1.1 411 Length Required\r\n")
+        );
+
+        let mut stream = Stream::new(b"POST /webhooks/sumup HTTP/1.1\r\nContent-Length: x\r\n\r\n");
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert!(stream.output.starts_with(b"HTTP/1.1 400 Bad Request\r\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn connection_failure() -> crate::Result {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+        let handler = |_: &crate::webhooks::Verified| -> Result<(), _> {
+            calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Err("failure".into())
+        };
+        let mut receiver = crate::webhooks::receiver::Receiver::new(Verifier, handler);
+        receiver.retry_delay = std::time::Duration::from_secs(60);
+
+        let request = format!(
+            "POST /webhooks/sumup HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            BODY.len(),
+            String::from_utf8_lossy(BODY)
+        );
+        let mut stream = Stream::new(request.as_bytes());
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert!(stream.output.starts_with(b"HTTP/1.1 500 "));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // The failed event isn't remembered, the redelivery is dispatched
+        let mut stream = Stream::new(request.as_bytes());
+        receiver.connection(&mut stream, "/webhooks/sumup")?;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn serve() -> crate::Result {
+        static CALLS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+        let handler = |_: &crate::webhooks::Verified| -> Result<(), _> {
+            CALLS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            Ok(())
+        };
+        // Served until the end of the tests
+        let receiver = Box::leak(Box::new(crate::webhooks::receiver::Receiver::new(
+            Verifier, handler,
+        )));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        std::thread::spawn(move || receiver.serve(&listener, "/webhooks/sumup"));
+
+        let clients = (0..4)
+            .map(|_| {
+                std::thread::spawn(move || -> std::io::Result<String> {
+                    use std::io::{Read as _, Write as _};
+
+                    let mut stream = std::net::TcpStream::connect(address)?;
+                    write!(
+                        stream,
+                        "POST /webhooks/sumup HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                        BODY.len(),
+                        String::from_utf8_lossy(BODY)
+                    )?;
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response)?;
+
+                    Ok(response)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for client in clients {
+            let response = client.join().unwrap()?;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        }
+
+        // Concurrent deliveries of the same event are dispatched once
+        assert_eq!(CALLS.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        Ok(())
+    }
+
+    struct Stream {
+        input: std::io::Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Stream {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: std::io::Cursor::new(input.to_vec()),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl std::io::Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl std::io::Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}